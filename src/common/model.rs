@@ -86,4 +86,36 @@ pub const READ_FROM_HEAD_FLAG: u64 = u64::MAX; // flag标识
 pub const DEFAULT_INCR_POLL_INTERVAL: Duration = Duration::from_millis(256); // 256ms
 
 pub const DEFAULT_FULL_POLL_INTERVAL: Duration = Duration::from_millis(512); // 512ms
-pub const DEFAULT_MAX_POLL_INTERVAL: Duration = Duration::from_millis(1024); // 1024ms
\ No newline at end of file
+pub const DEFAULT_MAX_POLL_INTERVAL: Duration = Duration::from_millis(1024); // 1024ms
+pub const DBF_STABLE_CHECK_INTERVAL: Duration = Duration::from_millis(50); // DBF文件稳定性检查间隔
+pub const CHECKPOINT_SAVE_INTERVAL: Duration = Duration::from_millis(1000); // 读取位置持久化的最小间隔
+
+/*
+FileIdentity: 文件身份标识(设备号+inode+创建时间), 用于判断文件是否被删除后重新生成
+平台不支持的字段为0
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FileIdentity {
+    pub dev: u64,
+    pub ino: u64,
+    pub created_ms: u64,
+}
+
+impl FileIdentity {
+    pub fn of(file_path: &PathBuf) -> anyhow::Result<Self> {
+        let meta = std::fs::metadata(file_path)?;
+        let created_ms = meta.created().ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            Ok(Self { dev: meta.dev(), ino: meta.ino(), created_ms })
+        }
+        #[cfg(not(unix))]
+        {
+            Ok(Self { dev: 0, ino: 0, created_ms })
+        }
+    }
+}
@@ -1,5 +1,7 @@
 pub mod reader;
 
+pub mod writer;
+
 pub mod notify;
 
 pub mod common;
@@ -0,0 +1,128 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll, Waker};
+use crossbeam::channel::{bounded, Receiver, SendError, Sender, TryRecvError, TrySendError};
+use futures_core::Stream;
+
+type SharedWaker = Arc<Mutex<Option<Waker>>>;
+
+/*
+AsyncSender: 异步订阅的发送端, 在读线程中发送后唤醒等待的任务
+*/
+pub struct AsyncSender<I> {
+    sender: Sender<I>,
+    waker: WakeOnDrop, // 字段按声明顺序drop: 先关闭sender再唤醒, 接收端被唤醒时能看到通道已关闭
+}
+
+impl<I> Clone for AsyncSender<I> {
+    fn clone(&self) -> Self {
+        Self { sender: self.sender.clone(), waker: WakeOnDrop(self.waker.0.clone()) }
+    }
+}
+
+impl<I> AsyncSender<I> {
+    /// 发送并唤醒接收端; 通道满时阻塞, 与同步订阅一致
+    pub fn send(&self, item: I) -> Result<(), SendError<I>> {
+        let result = self.sender.send(item);
+        self.wake();
+        result
+    }
+
+    /// 不等待, 通道满时返回Err
+    pub(crate) fn try_send(&self, item: I) -> Result<(), TrySendError<I>> {
+        self.sender.try_send(item)?;
+        self.wake();
+        Ok(())
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.sender.len()
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.sender.capacity().unwrap_or(usize::MAX)
+    }
+
+    fn wake(&self) {
+        self.waker.wake();
+    }
+}
+
+// 发送端drop时唤醒接收端, 最后一个发送端关闭后Stream结束
+struct WakeOnDrop(SharedWaker);
+
+impl WakeOnDrop {
+    fn wake(&self) {
+        if let Some(waker) = self.0.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Drop for WakeOnDrop {
+    fn drop(&mut self) {
+        self.wake();
+    }
+}
+
+/*
+AsyncReceiver: 异步订阅的接收端, 实现futures_core::Stream, 不额外创建线程
+取消订阅后(发送端全部关闭)Stream结束
+*/
+pub struct AsyncReceiver<I> {
+    receiver: Receiver<I>,
+    waker: SharedWaker,
+    alive: Arc<()>, // drop后发送端识别为已关闭
+}
+
+/// 创建异步订阅通道
+pub fn async_channel<I>(capacity: usize) -> (AsyncSender<I>, AsyncReceiver<I>) {
+    let (sender, receiver) = bounded(capacity);
+    let waker = Arc::new(Mutex::new(None));
+    (AsyncSender { sender, waker: WakeOnDrop(waker.clone()) }, AsyncReceiver { receiver, waker, alive: Arc::new(()) })
+}
+
+impl<I> AsyncReceiver<I> {
+    /// 接收下一项, 取消订阅后返回None; 可直接用于tokio::select!
+    pub fn recv(&mut self) -> impl Future<Output = Option<I>> + '_ {
+        std::future::poll_fn(move |cx| self.poll_recv(cx))
+    }
+
+    /// 不等待, 无数据时返回None
+    pub fn try_recv(&self) -> Option<I> {
+        self.receiver.try_recv().ok()
+    }
+
+    // 发送端按溢出策略取出通道中的项时使用
+    pub(crate) fn drain(&self) -> Receiver<I> {
+        self.receiver.clone()
+    }
+
+    // 持有drain时通道不会断开, 通过该标记判断接收端是否已drop
+    pub(crate) fn watch(&self) -> Weak<()> {
+        Arc::downgrade(&self.alive)
+    }
+
+    fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<I>> {
+        match self.receiver.try_recv() {
+            Ok(item) => return Poll::Ready(Some(item)),
+            Err(TryRecvError::Disconnected) => return Poll::Ready(None),
+            Err(TryRecvError::Empty) => {}
+        }
+        *self.waker.lock().unwrap() = Some(cx.waker().clone());
+        match self.receiver.try_recv() { // 注册waker后再检查一次, 避免丢失唤醒
+            Ok(item) => Poll::Ready(Some(item)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => Poll::Pending,
+        }
+    }
+}
+
+impl<I> Stream for AsyncReceiver<I> {
+    type Item = I;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<I>> {
+        self.poll_recv(cx)
+    }
+}
@@ -0,0 +1,455 @@
+use std::ops::Deref;
+use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use crossbeam::channel::{bounded, Receiver, SendError, Sender, TrySendError};
+use super::error::ReadResult;
+use super::stream::StreamItem;
+use super::batching::BatchLimits;
+#[cfg(feature = "async")]
+use super::async_stream::{AsyncReceiver, AsyncSender};
+
+const CONTROL_RETRY_INTERVAL: Duration = Duration::from_millis(1); // 控制事件等待通道空位的间隔
+
+/// 订阅通道满时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    #[default]
+    Block, // 阻塞读线程直到通道有空位, 同一文件的其他订阅者同时被阻塞
+    DropNewest, // 丢弃当前数据批次
+    DropOldest, // 丢弃通道中最早的数据批次后放入当前批次, 控制事件不丢弃
+    Coalesce, // 通道中的批次与当前批次合并, 合并后不超过批次行数上限; 仍放不下时丢弃最早的数据批次
+    Disconnect, // 断开订阅; 数据流订阅者先收到Disconnected, 之后通道关闭
+}
+
+/*
+SubscribeOptions: 订阅参数
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct SubscribeOptions {
+    pub capacity: usize, // 通道容量(批次数)
+    pub overflow: OverflowPolicy, // 通道满时的处理方式
+}
+
+impl Default for SubscribeOptions {
+    fn default() -> Self {
+        Self { capacity: 16, overflow: OverflowPolicy::Block }
+    }
+}
+
+impl SubscribeOptions {
+    /// 通道容量, 最小为1
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// 通道满时的处理方式
+    pub fn with_overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+}
+
+/// 订阅的溢出统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OverflowStats {
+    pub dropped_batches: u64, // 丢弃的批次数
+    pub dropped_rows: u64, // 丢弃的行数
+    pub coalesced_batches: u64, // 被合并的批次数
+}
+
+/// 推送项: 数据批次可合并, 数据流订阅者断开时收到通知
+pub trait Batch: Sized {
+    fn rows(&self) -> usize;
+
+    // 合并下一项, 无法合并时原样返回
+    fn merge(&mut self, next: Self) -> Option<Self>;
+
+    fn disconnected() -> Option<Self> {
+        None
+    }
+
+    // 控制事件, 溢出时不丢弃
+    fn is_control(&self) -> bool {
+        false
+    }
+}
+
+impl<T> Batch for Vec<ReadResult<T>> { // 包括Envelope批次
+    fn rows(&self) -> usize {
+        self.len()
+    }
+
+    fn merge(&mut self, next: Self) -> Option<Self> {
+        self.extend(next);
+        None
+    }
+}
+
+impl<T> Batch for StreamItem<T> {
+    fn rows(&self) -> usize {
+        match self {
+            StreamItem::Data(rows) => rows.len(),
+            _ => 0,
+        }
+    }
+
+    // 只合并相邻的数据批次, 控制事件保持原有顺序
+    fn merge(&mut self, next: Self) -> Option<Self> {
+        match (self, next) {
+            (StreamItem::Data(rows), StreamItem::Data(next)) => {
+                rows.extend(next);
+                None
+            }
+            (_, next) => Some(next),
+        }
+    }
+
+    fn disconnected() -> Option<Self> {
+        Some(StreamItem::Disconnected)
+    }
+
+    fn is_control(&self) -> bool {
+        !matches!(self, StreamItem::Data(_))
+    }
+}
+
+// 实际的发送通道
+enum Outlet<I> {
+    Sync(Sender<I>),
+    #[cfg(feature = "async")]
+    Async(AsyncSender<I>),
+}
+
+impl<I> Clone for Outlet<I> {
+    fn clone(&self) -> Self {
+        match self {
+            Outlet::Sync(sender) => Outlet::Sync(sender.clone()),
+            #[cfg(feature = "async")]
+            Outlet::Async(sender) => Outlet::Async(sender.clone()),
+        }
+    }
+}
+
+impl<I> Outlet<I> {
+    fn send(&self, item: I) -> Result<(), SendError<I>> {
+        match self {
+            Outlet::Sync(sender) => sender.send(item),
+            #[cfg(feature = "async")]
+            Outlet::Async(sender) => sender.send(item),
+        }
+    }
+
+    fn try_send(&self, item: I) -> Result<(), TrySendError<I>> {
+        match self {
+            Outlet::Sync(sender) => sender.try_send(item),
+            #[cfg(feature = "async")]
+            Outlet::Async(sender) => sender.try_send(item),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Outlet::Sync(sender) => sender.len(),
+            #[cfg(feature = "async")]
+            Outlet::Async(sender) => sender.len(),
+        }
+    }
+}
+
+/// 单次发送的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Delivery {
+    Sent, // 已放入通道(可能与通道中的批次合并)
+    Dropped, // 按溢出策略丢弃
+    Disconnected, // 按溢出策略断开, 需要移除订阅
+    Closed, // 接收端已关闭, 需要移除订阅
+}
+
+// 溢出计数
+#[derive(Default)]
+struct Counter {
+    dropped_batches: AtomicU64,
+    dropped_rows: AtomicU64,
+    coalesced_batches: AtomicU64,
+    disconnected: AtomicBool,
+}
+
+impl Counter {
+    fn drop_batch<I: Batch>(&self, item: &I) {
+        self.dropped_batches.fetch_add(1, Ordering::Relaxed);
+        self.dropped_rows.fetch_add(item.rows() as u64, Ordering::Relaxed);
+    }
+}
+
+/*
+OverflowSender: 按溢出策略发送的订阅通道, 除Block外通道满时不阻塞读线程
+DropOldest/Coalesce持有接收端的克隆, 用于取出通道中的批次; 通过接收端持有的标记判断订阅方是否已丢弃接收端
+*/
+pub struct OverflowSender<I> {
+    outlet: Outlet<I>,
+    drain: Option<Receiver<I>>,
+    alive: Option<Weak<()>>, // 订阅方的接收端全部drop后失效
+    policy: OverflowPolicy,
+    capacity: usize,
+    merge_rows: usize, // Coalesce合并后每批最多行数
+    counter: Arc<Counter>,
+}
+
+impl<I> Clone for OverflowSender<I> {
+    fn clone(&self) -> Self {
+        Self {
+            outlet: self.outlet.clone(),
+            drain: self.drain.clone(),
+            alive: self.alive.clone(),
+            policy: self.policy,
+            capacity: self.capacity,
+            merge_rows: self.merge_rows,
+            counter: self.counter.clone(),
+        }
+    }
+}
+
+// 直接传入的发送端按Block处理, 与原有行为一致
+impl<I> From<Sender<I>> for OverflowSender<I> {
+    fn from(sender: Sender<I>) -> Self {
+        let capacity = sender.capacity().unwrap_or(usize::MAX);
+        Self::new(Outlet::Sync(sender), None, OverflowPolicy::Block, capacity)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<I> From<AsyncSender<I>> for OverflowSender<I> {
+    fn from(sender: AsyncSender<I>) -> Self {
+        let capacity = sender.capacity();
+        Self::new(Outlet::Async(sender), None, OverflowPolicy::Block, capacity)
+    }
+}
+
+/*
+OverflowReceiver: 订阅通道的接收端, 可直接当作Receiver使用(recv/recv_timeout/Select)
+克隆共享同一标记, 全部drop后发送端识别为已关闭并移除订阅
+*/
+pub struct OverflowReceiver<I> {
+    receiver: Receiver<I>,
+    alive: Arc<()>,
+}
+
+impl<I> OverflowReceiver<I> {
+    // 标记随接收端drop
+    fn watch(&self) -> Weak<()> {
+        Arc::downgrade(&self.alive)
+    }
+}
+
+impl<I> Clone for OverflowReceiver<I> {
+    fn clone(&self) -> Self {
+        Self { receiver: self.receiver.clone(), alive: self.alive.clone() }
+    }
+}
+
+impl<I> Deref for OverflowReceiver<I> {
+    type Target = Receiver<I>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.receiver
+    }
+}
+
+impl<I> From<Receiver<I>> for OverflowReceiver<I> {
+    fn from(receiver: Receiver<I>) -> Self {
+        Self { receiver, alive: Arc::new(()) }
+    }
+}
+
+/// 按订阅参数创建通道; Disconnect多保留一个位置用于发送断开通知
+pub(crate) fn channel<I>(options: &SubscribeOptions) -> (OverflowSender<I>, OverflowReceiver<I>) {
+    let (sender, receiver) = bounded(slots(options));
+    let receiver = OverflowReceiver::from(receiver);
+    let mut overflow_sender = OverflowSender::new(Outlet::Sync(sender), None, options.overflow, options.capacity);
+    if needs_drain(options.overflow) {
+        overflow_sender.drain = Some(receiver.receiver.clone());
+        overflow_sender.alive = Some(receiver.watch());
+    }
+    (overflow_sender, receiver)
+}
+
+/// 按订阅参数创建异步通道
+#[cfg(feature = "async")]
+pub(crate) fn async_channel<I>(options: &SubscribeOptions) -> (OverflowSender<I>, AsyncReceiver<I>) {
+    let (sender, receiver) = super::async_stream::async_channel(slots(options));
+    let mut overflow_sender = OverflowSender::new(Outlet::Async(sender), None, options.overflow, options.capacity);
+    if needs_drain(options.overflow) {
+        overflow_sender.drain = Some(receiver.drain());
+        overflow_sender.alive = Some(receiver.watch());
+    }
+    (overflow_sender, receiver)
+}
+
+fn slots(options: &SubscribeOptions) -> usize {
+    match options.overflow {
+        OverflowPolicy::Disconnect => options.capacity + 1,
+        _ => options.capacity,
+    }
+}
+
+fn needs_drain(policy: OverflowPolicy) -> bool {
+    matches!(policy, OverflowPolicy::DropOldest | OverflowPolicy::Coalesce)
+}
+
+impl<I> OverflowSender<I> {
+    fn new(outlet: Outlet<I>, drain: Option<Receiver<I>>, policy: OverflowPolicy, capacity: usize) -> Self {
+        Self { outlet, drain, alive: None, policy, capacity, merge_rows: usize::MAX, counter: Arc::new(Counter::default()) }
+    }
+
+    /// 按批次上限限制Coalesce合并后的批次大小; 发送后的数据无原始记录长度, 只按行数限制
+    pub(crate) fn with_batch_limits(mut self, batch: &BatchLimits) -> Self {
+        self.merge_rows = batch.max_rows.unwrap_or(usize::MAX).max(1);
+        self
+    }
+
+    /// 持有接收端克隆, 接收端drop后可通过take_pending取回通道中未取走的批次
+    pub(crate) fn with_drain(mut self, receiver: &OverflowReceiver<I>) -> Self {
+        if self.drain.is_none() {
+            self.drain = Some(receiver.receiver.clone());
+            self.alive = Some(receiver.watch());
+        }
+        self
+    }
+
+    /// 取出通道中未取走的批次
+    pub(crate) fn take_pending(&self) -> Vec<I> {
+        self.drain.as_ref().map(|drain| drain.try_iter().collect()).unwrap_or_default()
+    }
+
+    /// 溢出统计
+    pub fn stats(&self) -> OverflowStats {
+        OverflowStats {
+            dropped_batches: self.counter.dropped_batches.load(Ordering::Relaxed),
+            dropped_rows: self.counter.dropped_rows.load(Ordering::Relaxed),
+            coalesced_batches: self.counter.coalesced_batches.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl<I: Batch> OverflowSender<I> {
+    /// 按溢出策略发送
+    pub(crate) fn send(&self, item: I) -> Delivery {
+        if self.counter.disconnected.load(Ordering::Relaxed) {
+            return Delivery::Disconnected;
+        }
+        if self.alive.as_ref().is_some_and(|alive| alive.strong_count() == 0) { // 持有接收端克隆时通道不会断开
+            return Delivery::Closed;
+        }
+        let item = match self.policy {
+            OverflowPolicy::Block => {
+                return match self.outlet.send(item) {
+                    Ok(()) => Delivery::Sent,
+                    Err(_) => Delivery::Closed,
+                };
+            }
+            OverflowPolicy::Disconnect if self.outlet.len() >= self.capacity => {
+                self.counter.disconnected.store(true, Ordering::Relaxed);
+                self.counter.drop_batch(&item);
+                if let Some(event) = I::disconnected() {
+                    let _ = self.outlet.try_send(event); // 使用预留的位置
+                }
+                return Delivery::Disconnected;
+            }
+            _ => match self.outlet.try_send(item) {
+                Ok(()) => return Delivery::Sent,
+                Err(TrySendError::Disconnected(_)) => return Delivery::Closed,
+                Err(TrySendError::Full(item)) => item,
+            },
+        };
+        match (self.policy, &self.drain) {
+            (OverflowPolicy::DropOldest, Some(drain)) => self.drop_oldest(drain, item),
+            (OverflowPolicy::Coalesce, Some(drain)) => self.coalesce(drain, item),
+            _ if item.is_control() => match self.outlet.send(item) { // 控制事件不丢弃, 等待通道空位
+                Ok(()) => Delivery::Sent,
+                Err(_) => Delivery::Closed,
+            },
+            _ => {
+                self.counter.drop_batch(&item);
+                Delivery::Dropped
+            }
+        }
+    }
+
+    /// 不阻塞发送: Block通道满时原样返回, 其他策略与send一致
+    pub(crate) fn try_send(&self, item: I) -> Result<Delivery, I> {
+        if self.policy != OverflowPolicy::Block {
+            return Ok(self.send(item));
+        }
+        if self.alive.as_ref().is_some_and(|alive| alive.strong_count() == 0) {
+            return Ok(Delivery::Closed);
+        }
+        match self.outlet.try_send(item) {
+            Ok(()) => Ok(Delivery::Sent),
+            Err(TrySendError::Full(item)) => Err(item),
+            Err(TrySendError::Disconnected(_)) => Ok(Delivery::Closed),
+        }
+    }
+
+    // 取出通道中的全部批次, 丢弃最早的数据批次后连同当前批次重新放入
+    fn drop_oldest(&self, drain: &Receiver<I>, item: I) -> Delivery {
+        let items = drain.try_iter().chain(std::iter::once(item)).collect();
+        self.refill(items)
+    }
+
+    // 取出通道中的全部批次, 相邻批次在行数上限内合并后重新放入
+    fn coalesce(&self, drain: &Receiver<I>, item: I) -> Delivery {
+        let mut merged: Vec<I> = vec![];
+        for next in drain.try_iter().chain(std::iter::once(item)) {
+            let rest = match merged.last_mut() {
+                Some(last) if last.rows().saturating_add(next.rows()) <= self.merge_rows => last.merge(next),
+                _ => Some(next),
+            };
+            match rest {
+                Some(next) => merged.push(next),
+                None => { self.counter.coalesced_batches.fetch_add(1, Ordering::Relaxed); }
+            }
+        }
+        self.refill(merged) // 达到行数上限或控制事件过多无法合并时丢弃最早的数据批次
+    }
+
+    // 按原顺序放回通道: 超出容量时丢弃最早的数据批次, 控制事件总是放入(通道满时等待订阅方取走)
+    fn refill(&self, items: Vec<I>) -> Delivery {
+        let mut overflow = items.len().saturating_sub(self.capacity);
+        for next in items {
+            if next.is_control() {
+                if let Delivery::Closed = self.send_control(next) {
+                    return Delivery::Closed;
+                }
+                continue;
+            }
+            if overflow > 0 {
+                overflow -= 1;
+                self.counter.drop_batch(&next);
+                continue;
+            }
+            match self.outlet.try_send(next) {
+                Ok(()) => {}
+                Err(TrySendError::Full(next)) => self.counter.drop_batch(&next),
+                Err(TrySendError::Disconnected(_)) => return Delivery::Closed,
+            }
+        }
+        Delivery::Sent
+    }
+
+    // 持有接收端克隆时通道不会断开, 等待期间通过标记判断订阅方是否已丢弃接收端
+    fn send_control(&self, mut item: I) -> Delivery {
+        loop {
+            match self.outlet.try_send(item) {
+                Ok(()) => return Delivery::Sent,
+                Err(TrySendError::Full(back)) => item = back,
+                Err(TrySendError::Disconnected(_)) => return Delivery::Closed,
+            }
+            if self.alive.as_ref().is_some_and(|alive| alive.strong_count() == 0) {
+                return Delivery::Closed;
+            }
+            std::thread::sleep(CONTROL_RETRY_INTERVAL);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::ops::Range;
+use std::time::Duration;
+
+/*
+BatchLimits: 单次发送的批次上限, 超出时按顺序拆分为多个批次发送, 避免补发时单个批次过大
+订阅方在Select中处理多个通道时, 可在批次之间处理其他通道
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BatchLimits {
+    pub max_rows: Option<usize>, // 每批最多行数
+    pub max_bytes: Option<usize>, // 每批最多字节数, 按原始记录长度估算; 单行超出时单独成批
+    pub linger: Option<Duration>, // 收到文件变动通知后等待该时间再读取, 期间的多次写入合并为一次读取
+}
+
+impl BatchLimits {
+    /// 将len行按上限拆分为连续区间, 每批至少一行; size_of为第i行的字节数
+    pub fn split<S: Fn(usize) -> usize>(&self, len: usize, size_of: S) -> Vec<Range<usize>> {
+        let max_rows = self.max_rows.unwrap_or(usize::MAX).max(1);
+        let max_bytes = self.max_bytes.unwrap_or(usize::MAX);
+        let mut ranges = vec![];
+        let (mut start, mut bytes) = (0, 0_usize);
+        for i in 0..len {
+            let size = size_of(i);
+            if i > start && (i - start >= max_rows || bytes.saturating_add(size) > max_bytes) {
+                ranges.push(start..i);
+                (start, bytes) = (i, 0);
+            }
+            bytes = bytes.saturating_add(size);
+        }
+        if start < len || ranges.is_empty() { // 无数据时仍发送一个空批次, 与不拆分时一致
+            ranges.push(start..len);
+        }
+        ranges
+    }
+}
@@ -0,0 +1,208 @@
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::common::model::{FileIdentity, CHECKPOINT_SAVE_INTERVAL};
+use crate::common::timer::get_coarse_timestamp_ms;
+
+/*
+Checkpoint: 持久化的读取位置
+seek_pos含义与SubsReader.seek_pos一致(CSV为字节偏移, DBF为记录序号)
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub identity: FileIdentity, // 保存时的文件身份
+    pub seek_pos: u64, // 读取位置
+    pub update_time: u64, // 保存时间(ms)
+}
+
+/// 读取位置存储
+pub trait CheckpointStore: Send + Sync {
+    /// 读取, 不存在返回None
+    fn load(&self, key: &str) -> Result<Option<Checkpoint>>;
+    /// 保存(覆盖)
+    fn save(&self, key: &str, checkpoint: &Checkpoint) -> Result<()>;
+}
+
+// 文件存储的行格式(csv不支持嵌套结构)
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckpointRecord {
+    dev: u64,
+    ino: u64,
+    created_ms: u64,
+    seek_pos: u64,
+    update_time: u64,
+}
+
+/*
+FileCheckpointStore: 默认的文件存储, 每个key一个文件; 先写临时文件再rename, 保证不会读到半截内容
+*/
+pub struct FileCheckpointStore {
+    pub dir: PathBuf, // 存储目录
+}
+
+impl FileCheckpointStore {
+    pub fn new(dir: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    // key转为文件名: 可读部分+hash, 避免非法字符及截断后冲突
+    fn checkpoint_path(&self, key: &str) -> PathBuf {
+        let mut hasher = rustc_hash::FxHasher::default();
+        key.hash(&mut hasher);
+        let readable = key.chars()
+            .rev().take(48).collect::<Vec<_>>().into_iter().rev()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+            .collect::<String>();
+        self.dir.join(format!("{}_{:016x}.ckpt", readable, hasher.finish()))
+    }
+}
+
+impl CheckpointStore for FileCheckpointStore {
+    fn load(&self, key: &str) -> Result<Option<Checkpoint>> {
+        let path = self.checkpoint_path(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let mut reader = csv::ReaderBuilder::new().has_headers(false).from_path(&path)?;
+        match reader.deserialize::<CheckpointRecord>().next() {
+            Some(record) => {
+                let record = record?;
+                Ok(Some(Checkpoint {
+                    identity: FileIdentity { dev: record.dev, ino: record.ino, created_ms: record.created_ms },
+                    seek_pos: record.seek_pos,
+                    update_time: record.update_time,
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn save(&self, key: &str, checkpoint: &Checkpoint) -> Result<()> {
+        let path = self.checkpoint_path(key);
+        let tmp_path = path.with_extension("ckpt.tmp");
+        let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(vec![]);
+        writer.serialize(CheckpointRecord {
+            dev: checkpoint.identity.dev,
+            ino: checkpoint.identity.ino,
+            created_ms: checkpoint.identity.created_ms,
+            seek_pos: checkpoint.seek_pos,
+            update_time: checkpoint.update_time,
+        })?;
+        let data = writer.into_inner().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let mut fd = std::fs::File::create(&tmp_path)?;
+        fd.write_all(&data)?;
+        fd.sync_all()?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+}
+
+/*
+ReaderCheckpoint: 单个reader的读取位置存取
+*/
+#[derive(Clone)]
+pub struct ReaderCheckpoint {
+    pub store: Arc<dyn CheckpointStore>,
+    pub key: String,
+    pub file_path: PathBuf,
+}
+
+impl ReaderCheckpoint {
+    /// 文件身份与保存时一致才返回保存的位置, 否则从头读
+    pub fn load_seek_pos(&self) -> u64 {
+        let identity = match FileIdentity::of(&self.file_path) {
+            Ok(identity) => identity,
+            Err(e) => {
+                ::ftlog::error!("{} get file identity error: {:?}", self.file_path.display(), e);
+                return 0;
+            }
+        };
+        match self.store.load(&self.key) {
+            Ok(Some(checkpoint)) if checkpoint.identity == identity => {
+                ::ftlog::info!("{} resume from checkpoint seek pos {}", self.file_path.display(), checkpoint.seek_pos);
+                checkpoint.seek_pos
+            }
+            Ok(Some(checkpoint)) => {
+                ::ftlog::info!("{} identity changed {:?} -> {:?}, start fresh", self.file_path.display(), checkpoint.identity, identity);
+                0
+            }
+            Ok(None) => 0,
+            Err(e) => {
+                ::ftlog::error!("{} load checkpoint error: {:?}", self.file_path.display(), e);
+                0
+            }
+        }
+    }
+
+    /// 保存读取位置, 失败仅记录日志
+    pub fn save(&self, seek_pos: u64) {
+        let result = FileIdentity::of(&self.file_path).and_then(|identity| {
+            self.store.save(&self.key, &Checkpoint { identity, seek_pos, update_time: get_coarse_timestamp_ms() })
+        });
+        if let Err(e) = result {
+            ::ftlog::error!("{} save checkpoint error: {:?}", self.file_path.display(), e);
+        }
+    }
+}
+
+/*
+ThrottledCheckpoint: 读线程使用, 按最小间隔保存读取位置(每次保存都需要fsync+rename);
+间隔内的位置暂不保存, 到期后由读线程写入(文件不再变动时也会保存), 或在下一次保存、drop时写入;
+进程异常退出时最多重复推送最近一个间隔内的数据
+*/
+pub(crate) struct ThrottledCheckpoint {
+    checkpoint: Option<ReaderCheckpoint>,
+    interval: Duration,
+    last_save: Option<Instant>, // 上次实际保存的时间
+    unsaved: Option<u64>, // 未保存的位置
+}
+
+impl ThrottledCheckpoint {
+    pub(crate) fn new(checkpoint: Option<ReaderCheckpoint>) -> Self {
+        Self { checkpoint, interval: CHECKPOINT_SAVE_INTERVAL, last_save: None, unsaved: None }
+    }
+
+    /// 数据分发后调用; 距上次保存不足间隔时延后保存
+    pub(crate) fn save(&mut self, seek_pos: u64) {
+        if self.last_save.is_some_and(|last| last.elapsed() < self.interval) {
+            self.unsaved = Some(seek_pos);
+        } else {
+            self.save_now(seek_pos);
+        }
+    }
+
+    /// 延后保存的位置的剩余等待时间, 读线程以此作为等待事件的超时; 没有未保存的位置时为None
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        self.unsaved?;
+        Some(self.last_save.map_or(Duration::ZERO, |last| self.interval.saturating_sub(last.elapsed())))
+    }
+
+    /// 读线程每次等待事件后调用: 未保存的位置到期后写入
+    pub(crate) fn flush_due(&mut self) {
+        if let Some(seek_pos) = self.unsaved && self.last_save.is_none_or(|last| last.elapsed() >= self.interval) {
+            self.save_now(seek_pos);
+        }
+    }
+
+    /// 立即保存(如文件重置)
+    pub(crate) fn save_now(&mut self, seek_pos: u64) {
+        if let Some(checkpoint) = &self.checkpoint {
+            checkpoint.save(seek_pos);
+            self.last_save = Some(Instant::now());
+        }
+        self.unsaved = None;
+    }
+}
+
+impl Drop for ThrottledCheckpoint {
+    fn drop(&mut self) {
+        if let Some(seek_pos) = self.unsaved.take() {
+            self.save_now(seek_pos);
+        }
+    }
+}
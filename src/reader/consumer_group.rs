@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use rustc_hash::FxHashMap;
+use super::backpressure::{Delivery, OverflowSender};
+use super::envelope::RowData;
+use super::error::ReadResult;
+use super::msg_dispatcher::{CertKeyT, Filter, KeyFn};
+use super::batching::BatchLimits;
+
+const PENDING_RETRY_INTERVAL: Duration = Duration::from_millis(1); // 暂存超出上限时等待成员取走数据的间隔
+
+/// 消费组暂存行数达到上限时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PendingOverflow {
+    #[default]
+    Block, // 阻塞读线程直到成员取走数据, 不丢弃; 组内无成员时不阻塞, 暂存不受上限限制
+    DropNewest, // 丢弃新到的行
+    DropOldest, // 丢弃最早暂存的行
+}
+
+/*
+GroupOptions: 消费组参数
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct GroupOptions {
+    pub max_pending: usize, // 暂存行数上限(无成员或成员通道满时暂存)
+    pub overflow: PendingOverflow, // 暂存达到上限时的处理方式
+}
+
+impl Default for GroupOptions {
+    fn default() -> Self {
+        Self { max_pending: 100_000, overflow: PendingOverflow::Block }
+    }
+}
+
+impl GroupOptions {
+    /// 暂存行数上限, 最小为1
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = max_pending.max(1);
+        self
+    }
+
+    /// 暂存达到上限时的处理方式
+    pub fn with_overflow(mut self, overflow: PendingOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+}
+
+/*
+ConsumerGroup: 竞争消费组, 每行只发给组内一个成员
+按分区key(如订单号)分配成员, key首次出现时轮询分配, 之后固定发给该成员, 同一key的数据在一个成员上保持顺序
+成员通道满或组内无成员时暂存(按GroupOptions限制行数), 下次分发或成员变化时按原顺序重试
+成员退出(含detach后取消订阅)时取回其通道中未取走的批次, 连同其负责的key重新分配给其他成员
+*/
+pub struct ConsumerGroup<T> {
+    pub name: String,
+    filter: Filter<T>, // 组的过滤条件
+    partition: KeyFn<T>, // 分区key提取函数
+    batch: BatchLimits, // 发给成员的批次上限
+    options: GroupOptions,
+    delivery: Mutex<()>, // 串行投递, 保证同一key的顺序; 投递不阻塞, 不会因成员通道满而长期持有
+    inner: Mutex<GroupState<T>>,
+}
+
+struct GroupMember<T> {
+    cert_key: CertKeyT,
+    sender: OverflowSender<Vec<ReadResult<T>>>,
+}
+
+struct GroupState<T> {
+    members: Vec<GroupMember<T>>, // 按加入顺序
+    assign: FxHashMap<String, CertKeyT>, // 分区key -> 成员
+    next: usize, // 新key轮询分配
+    pending: VecDeque<RowData<T>>, // 暂存的行
+    dropped: u64, // 暂存超出上限丢弃的行数
+}
+
+impl<T: Clone> ConsumerGroup<T> {
+    pub fn new(name: &str, filter: Filter<T>, partition: KeyFn<T>, batch: BatchLimits, options: GroupOptions) -> Self {
+        Self {
+            name: name.to_string(),
+            filter,
+            partition,
+            batch,
+            options,
+            delivery: Mutex::new(()),
+            inner: Mutex::new(GroupState { members: vec![], assign: FxHashMap::default(), next: 0, pending: VecDeque::new(), dropped: 0 }),
+        }
+    }
+
+    /// 加入成员, 暂存的行发给组内成员; 返回接收端已关闭的成员
+    pub fn join(&self, cert_key: CertKeyT, sender: OverflowSender<Vec<ReadResult<T>>>) -> Vec<CertKeyT> {
+        let _delivery = self.delivery.lock().unwrap();
+        let members = {
+            let mut state = self.inner.lock().unwrap();
+            state.members.push(GroupMember { cert_key, sender });
+            state.members.len()
+        };
+        ::ftlog::info!("group {} join cert={}, members={}", self.name, cert_key, members);
+        self.route(vec![])
+    }
+
+    /// 移除成员, 返回是否为组内成员; 其通道中未取走的行(发送端持有接收端时)与其负责的key一起重新分配
+    pub fn leave(&self, cert_key: CertKeyT) -> (bool, Vec<CertKeyT>) {
+        let _delivery = self.delivery.lock().unwrap(); // 等待投递完成, 之后不会再向该成员发送
+        let member = {
+            let mut state = self.inner.lock().unwrap();
+            let Some(index) = state.members.iter().position(|member| member.cert_key == cert_key) else {
+                return (false, vec![]);
+            };
+            state.assign.retain(|_, owner| *owner != cert_key);
+            state.members.remove(index)
+        };
+        let rows = member.sender.take_pending().into_iter().flatten().map(RowData::detached).collect::<Vec<_>>();
+        ::ftlog::info!("group {} leave cert={}, redistribute {} rows", self.name, cert_key, rows.len());
+        {
+            let mut state = self.inner.lock().unwrap();
+            for row in rows.into_iter().rev() { // 早于暂存的行
+                state.pending.push_front(row);
+            }
+        }
+        (true, self.route(vec![]))
+    }
+
+    /// 分发读取的数据行, 只处理满足组过滤条件的行; 错误数据轮询发给成员; 返回接收端已关闭的成员
+    /// Block时暂存超出上限则等待成员取走数据, 等待期间释放投递锁, 成员可加入或退出
+    pub fn dispatch<D: Fn(&RowData<T>) -> bool>(&self, rows: &[RowData<T>], deliverable: D) -> Vec<CertKeyT> {
+        let rows = rows.iter()
+            .filter(|row| deliverable(row) && row.data.as_ref().map_or(true, |data| (self.filter)(data)))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut closed = {
+            let _delivery = self.delivery.lock().unwrap();
+            if rows.is_empty() && self.inner.lock().unwrap().pending.is_empty() {
+                return vec![];
+            }
+            self.route(rows)
+        };
+        while closed.is_empty() && self.blocked() { // 有成员关闭时先返回, 移除后再继续
+            thread::sleep(PENDING_RETRY_INTERVAL);
+            let _delivery = self.delivery.lock().unwrap();
+            closed = self.route(vec![]);
+        }
+        closed
+    }
+
+    /// 成员数量
+    pub fn members(&self) -> usize {
+        self.inner.lock().unwrap().members.len()
+    }
+
+    /// 暂存的行数
+    pub fn pending(&self) -> usize {
+        self.inner.lock().unwrap().pending.len()
+    }
+
+    /// 暂存超出上限丢弃的行数
+    pub fn dropped(&self) -> u64 {
+        self.inner.lock().unwrap().dropped
+    }
+
+    // Block时有成员且暂存超出上限, 需等待成员取走数据
+    fn blocked(&self) -> bool {
+        if self.options.overflow != PendingOverflow::Block {
+            return false;
+        }
+        let state = self.inner.lock().unwrap();
+        !state.members.is_empty() && state.pending.len() > self.options.max_pending
+    }
+
+    // 持有组锁按key分配成员, 释放后逐个成员不阻塞发送; 未能放入通道的行按原顺序放回暂存
+    // 调用方持有delivery锁
+    fn route(&self, rows: Vec<RowData<T>>) -> Vec<CertKeyT> {
+        let batches = {
+            let mut state = self.inner.lock().unwrap();
+            let mut rows = state.pending.drain(..).chain(rows).collect::<VecDeque<_>>();
+            if state.members.is_empty() {
+                state.pending = rows;
+                self.limit_pending(&mut state);
+                return vec![];
+            }
+            let mut batches: FxHashMap<CertKeyT, Vec<RowData<T>>> = FxHashMap::default();
+            for row in rows.drain(..) {
+                let key = row.data.as_ref().ok().map(|data| (self.partition)(data));
+                let owner = match key.and_then(|key| state.assign.get(key)) {
+                    Some(owner) => *owner,
+                    None => { // 新key或错误数据轮询分配
+                        let owner = state.members[state.next % state.members.len()].cert_key;
+                        state.next += 1;
+                        if let Some(key) = key {
+                            state.assign.insert(key.to_string(), owner);
+                        }
+                        owner
+                    }
+                };
+                batches.entry(owner).or_default().push(row);
+            }
+            state.members.iter()
+                .filter_map(|member| batches.remove(&member.cert_key).map(|batch| (member.cert_key, member.sender.clone(), batch)))
+                .collect::<Vec<_>>()
+        };
+
+        let mut closed = vec![];
+        let mut unsent = vec![];
+        for (cert_key, sender, batch) in batches {
+            for range in self.batch.split(batch.len(), |i| batch[i].size) {
+                let msgs = batch[range.clone()].iter().map(|row| row.data.clone()).collect();
+                match sender.try_send(msgs) {
+                    Ok(Delivery::Sent) => {}
+                    Ok(Delivery::Dropped) => ::ftlog::warn!("group {} cert={} channel full, batch dropped", self.name, cert_key),
+                    Ok(Delivery::Disconnected | Delivery::Closed) => { // 移除成员后重新分配
+                        closed.push(cert_key);
+                        unsent.extend_from_slice(&batch[range.start..]);
+                        break;
+                    }
+                    Err(_) => { // 通道满, 之后的批次也暂存以保持顺序
+                        unsent.extend_from_slice(&batch[range.start..]);
+                        break;
+                    }
+                }
+            }
+        }
+        if !unsent.is_empty() {
+            let mut state = self.inner.lock().unwrap();
+            ::ftlog::debug!("group {} {} rows pending", self.name, unsent.len());
+            for row in unsent.into_iter().rev() {
+                state.pending.push_front(row);
+            }
+            self.limit_pending(&mut state);
+        }
+        closed
+    }
+
+    // 暂存超出上限时按策略丢弃, Block由dispatch等待
+    fn limit_pending(&self, state: &mut GroupState<T>) {
+        let overflow = state.pending.len().saturating_sub(self.options.max_pending);
+        if overflow == 0 {
+            return;
+        }
+        match self.options.overflow {
+            PendingOverflow::Block => return,
+            PendingOverflow::DropNewest => state.pending.truncate(self.options.max_pending),
+            PendingOverflow::DropOldest => { state.pending.drain(..overflow); }
+        }
+        state.dropped += overflow as u64;
+        ::ftlog::warn!("group {} pending full, {} rows dropped", self.name, overflow);
+    }
+}
@@ -1,15 +1,32 @@
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::{PathBuf};
+use std::sync::Arc;
+use std::cell::OnceCell;
 use serde::{Deserialize};
-use std::sync::atomic::Ordering;
-use anyhow::{Result};
-use encoding_rs_io::{DecodeReaderBytesBuilder};
+use std::sync::atomic::{AtomicU64, Ordering};
 use crate::common::model::{*};
 use crate::common::timer::get_coarse_timestamp_ms;
 use super::{subscribe_reader::*};
+use super::msg_dispatcher::{MsgDispatcher, CertKeyT};
+use super::checkpoint::ThrottledCheckpoint;
+use super::durable::DurableCursors;
+use super::retention::RetentionBuffer;
+use super::envelope::{ReadContext, RowData};
+use super::error::ReadError;
+use super::stream::{HeartbeatTimer, StreamItem};
 use crossbeam::channel::Select;
 
+/*
+CsvChunk: 从seek位置读到文件尾的结果
+*/
+struct CsvChunk<T> {
+    end_pos: u64, // 读取后的位置
+    end_line: u64, // 读取后位置之前的行数
+    rows: Vec<RowData<T>>,
+    read_success: bool, // 最后一行是否完整(失败可能是行未写完)
+}
+
 impl <T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> SubsReader<T, CSV> {
 
     // 启动
@@ -20,24 +37,69 @@ impl <T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> SubsReader<T,
         let seek_pos = self.seek_pos.clone();
         let enc_type = self.enc_type;
         let dispatcher = self.msg_dispatcher.clone();
+        let checkpoint = self.checkpoint.clone();
+        let durable = self.durable.clone();
+        let retention = self.retention.clone();
+        let heartbeat_interval = self.options.heartbeat_interval;
 
         let recv_notify_signal_chan = self.notify_meta.receiver.clone();
         let recv_read_signal_chan = self.inner_chan.1.clone();
 
         std::thread::spawn(move || {
             ::ftlog::info!("{} csv_reader thread start", file_path.display());
-            let mut _last_read_size = 0; // 上次读取字节数
+            let shared_path = Arc::new(file_path.clone()); // 填入Envelope
+            let mut last_read = LastRead {
+                retention,
+                seek_line: (u64::MAX, 0),
+                checkpoint: ThrottledCheckpoint::new(checkpoint),
+            };
+            let replay = |cert_key: CertKeyT, from: &ReplayFrom<T>| { // 从回放起点读到当前位置, 仅发给该订阅者
+                let end_pos = if is_increment { Some(seek_pos.load(Ordering::Relaxed)) } else { None };
+                match read_csv_replay::<T>(&file_path, from, end_pos, enc_type) {
+                    Ok(rows) => {
+                        ::ftlog::info!("{} replay data len: {}", file_path.display(), rows.len());
+                        let ctx = ReadContext::now(&shared_path);
+                        let ctx = match rows.first() { // 回放的行从文件读取, 第一行的偏移即读取起点
+                            Some(row) => ctx.with_read_from(row.offset),
+                            None => ctx,
+                        };
+                        if let Err(e) = dispatcher.send_to(&rows, &ctx, cert_key) {
+                            ::ftlog::error!("replay send error: {:?}", e);
+                        }
+                    }
+                    Err(e) => {
+                        ::ftlog::error!("replay read error: {:?}", e);
+                    }
+                }
+            };
+            let mut last_identity = FileIdentity::of(&file_path).unwrap_or_default(); // 用于识别文件替换
+            let mut last_fields = read_csv_headers(&file_path, enc_type); // 用于识别表头变化
+            let mut heartbeat = HeartbeatTimer::new(heartbeat_interval);
             let mut _last_read_time = 0_u64; // 上次读取时间 (避免read间隔太频繁)
             let mut selector = Select::new();
             let notify_idx = selector.recv(&recv_notify_signal_chan);
             let read_idx = selector.recv(&recv_read_signal_chan);
 
             while is_running.load(Ordering::Relaxed) {
-                
-                let select_idx = selector.select();
+                if stop_if_no_subscriber(&dispatcher, &is_running) { // 接收端全部关闭
+                    break;
+                }
+
+                let timeout = heartbeat.timeout().into_iter().chain(last_read.checkpoint.timeout()).min(); // 心跳或延后保存的位置到期
+                let select_idx = match timeout {
+                    Some(timeout) => selector.select_timeout(timeout).ok(),
+                    None => Some(selector.select()),
+                };
+                last_read.checkpoint.flush_due();
+                if heartbeat.is_due() {
+                    dispatcher.broadcast_event(StreamItem::Heartbeat { time: get_coarse_timestamp_ms(), pos: seek_pos.load(Ordering::Relaxed) });
+                }
+                let Some(select_idx) = select_idx else {
+                    continue; // 等待超时
+                };
 
                 match select_idx.index() {
-                   /* 文件变动事件 */ 
+                   /* 文件变动事件 */
                    i if i == notify_idx => {
                         // 文件变动通知
                         let mut need_read_data = false;
@@ -59,11 +121,49 @@ impl <T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> SubsReader<T,
                                 ::ftlog::error!("recv signal error: {:?}", e);
                             }
                         }
-                        
+
                         let cur_seek_pos = seek_pos.load(Ordering::Relaxed); // 当前文件seek位置
                         if !need_read_data {
                             continue;
                         }
+                        linger_notify(dispatcher.batch_limits().linger, &recv_notify_signal_chan);
+
+                        // 文件被替换或截断时从头读取; 文件不存在(可能正在重新生成)时等待下一次通知
+                        let (file_len, identity) = match (std::fs::metadata(&file_path), FileIdentity::of(&file_path)) {
+                            (Ok(meta), Ok(identity)) => (meta.len(), identity),
+                            _ => {
+                                ::ftlog::info!("{} not found, wait next notify", file_path.display());
+                                continue;
+                            }
+                        };
+                        let rotated = last_identity != FileIdentity::default() && identity != last_identity;
+                        if rotated || file_len < cur_seek_pos {
+                            ::ftlog::info!("{} rotated={} len={} seek={}", file_path.display(), rotated, file_len, cur_seek_pos);
+                            if !cfg!(feature = "reset_seek_when_err") {
+                                continue;
+                            }
+                            ::ftlog::info!("{} reset seek pos", file_path.display());
+                            seek_pos.store(0, Ordering::Release);
+                            last_read.checkpoint.save_now(0);
+                            durable.on_reset();
+                            dispatcher.on_reset();
+                            last_read.retention.clear(0);
+                            last_read.seek_line = (0, 0);
+                            dispatcher.broadcast_event(if rotated {
+                                StreamItem::FileRotated { old: last_identity, new: identity }
+                            } else {
+                                StreamItem::FileReset { identity }
+                            });
+                            let fields = read_csv_headers(&file_path, enc_type);
+                            if fields != last_fields {
+                                dispatcher.broadcast_event(StreamItem::SchemaChanged { fields: fields.clone() });
+                                last_fields = fields;
+                            }
+                        }
+                        last_identity = identity;
+                        if file_len == seek_pos.load(Ordering::Relaxed) { // 无新数据
+                            continue;
+                        }
 
                         if cur_read_time  < _last_read_time + MIN_READ_INTERVAL { // 避免read间隔太频繁
                             ::ftlog::debug!("sleep 1ms for read");
@@ -71,251 +171,394 @@ impl <T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> SubsReader<T,
                         }
                         _last_read_time = get_coarse_timestamp_ms();
 
-                        match retry_read_from_seek::<T>(&file_path, cur_seek_pos, enc_type, MAX_READ_RETRY_TIME) {
-                            Ok((new_seek_pos, datas, is_read_success)) => {
-                                if !is_read_success { // 可能存在文件删除后重新生成的情况
-                                    ::ftlog::info!("{} retry read error.", file_path.display());
-                                    #[cfg(feature = "reset_seek_when_err")] {
-                                        ::ftlog::info!("{} retry read error. reset seek pos", file_path.display());
-                                        seek_pos.store(0, Ordering::Release);
-                                    }
-                                    continue;
-                                }
-                                if is_increment  && is_read_success { // 增量读; 需要更新POS 
-                                    _last_read_size = new_seek_pos - cur_seek_pos;
-                                    seek_pos.store(new_seek_pos, Ordering::Relaxed);
-                                }
-                                let data_len = datas.len();
-                                ::ftlog::info!("read data len: {}", data_len);
-                                match dispatcher.dispatch(datas) { // 分发数据
-                                    Ok(_) => {
-                                        ::ftlog::debug!("dispatch success;len={}", data_len);
-                                    }
-                                    Err(e) => {
-                                        ::ftlog::error!("dispatch error: {:?}", e);
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                ::ftlog::error!("read error: {:?}", e);
-                            }
-                        }
-                    
+                        read_and_dispatch(&ReadContext::new(&shared_path, cur_read_time), enc_type, is_increment, &seek_pos, &dispatcher, &mut last_read, &durable);
                     }
-                   /* 读请求事件 */ 
+                   /* 读请求事件 */
                    i if i == read_idx => {
                         match select_idx.recv(&recv_read_signal_chan) { // 接受该事件
-                            Ok((cert_key, _seek_pos)) => {
-                                if _seek_pos == READ_FROM_HEAD_FLAG { // 用MAX代表
+                            Ok(request) => match request {
+                                ReadRequest::FromHead => {
                                     ::ftlog::info!("{} read from head", file_path.display());
-                                    let cur_seek_pos = seek_pos.load(Ordering::Relaxed); // 当前文件seek位置;
-                                    match retry_read_from_seek::<T>(&file_path, cur_seek_pos, enc_type, MAX_READ_RETRY_TIME) {
-                                        Ok((new_seek_pos, datas, is_read_success)) => {
-                                            if !is_read_success {
-                                                ::ftlog::error!("{} read error", file_path.display());
-                                                continue;
-                                            }
-                                            if is_increment { // 增量读; 需要更新POS 
-                                                _last_read_size = new_seek_pos - cur_seek_pos;
-                                                seek_pos.store(new_seek_pos, Ordering::Relaxed);
-                                            }
-                                            let data_len = datas.len();
-                                            ::ftlog::info!("read data len: {}", data_len);
-                                            match dispatcher.dispatch(datas) { // 分发数据
-                                                Ok(_) => {
-                                                    ::ftlog::debug!("dispatch success;len={}", data_len);
-                                                }
-                                                Err(e) => {
-                                                    ::ftlog::error!("dispatch error: {:?}", e);
-                                                }
-                                            }
-                                        }
-                                        Err(e) => {
-                                            ::ftlog::error!("read error: {:?}", e);
-                                        }
+                                    read_and_dispatch(&ReadContext::now(&shared_path), enc_type, is_increment, &seek_pos, &dispatcher, &mut last_read, &durable);
+                                }
+                                ReadRequest::DurableActivate => { // 具名订阅补发
+                                    durable.catch_up(seek_pos.load(Ordering::Relaxed), &dispatcher, &ReadContext::now(&shared_path), |begin, end| last_read.read(&file_path, begin, end, enc_type));
+                                }
+                                ReadRequest::Replay(cert_key, from) => {
+                                    replay(cert_key, &from);
+                                }
+                                ReadRequest::Resume(cert_key) => { // 先补发暂停期间的数据, 之后接收实时数据
+                                    if let Some(offset) = dispatcher.resume(cert_key, &ReadContext::now(&shared_path)) {
+                                        replay(cert_key, &ReplayFrom::ByteOffset(offset));
                                     }
-                    
                                 }
-                                else {
+                                ReadRequest::ToPos(cert_key, _seek_pos) => {
                                     ::ftlog::info!("{} read from 0 to {}", file_path.display(), _seek_pos);
-                                    let from_zero_data = read_to_seek::<T>(&file_path, _seek_pos, enc_type);
+                                    let from_zero_data = last_read.read(&file_path, 0, _seek_pos, enc_type);
                                     if from_zero_data.is_err() {
                                         // 读失败则不尝试重试
                                         ::ftlog::error!("read error: {:?}", from_zero_data.err());
                                         continue;
                                     }
                                     let from_zero_data = from_zero_data.unwrap(); // [safe] not err
-                                    let _= dispatcher.dispatch_single(from_zero_data, cert_key);
+                                    let _= dispatcher.dispatch_single(&from_zero_data, &ReadContext::now(&shared_path).with_read_from(0), cert_key);
+                                }
+                                ReadRequest::CaughtUp(cert_key) => {
+                                    dispatcher.send_event(cert_key, StreamItem::CaughtUp { pos: seek_pos.load(Ordering::Relaxed) });
+                                }
+                                ReadRequest::Snapshot(cert_key) => { // 读线程是唯一推进seek的地方, 快照结束位置即实时数据起点
+                                    send_snapshot(&dispatcher, cert_key, last_read.read(&file_path, 0, seek_pos.load(Ordering::Relaxed), enc_type), &ReadContext::now(&shared_path));
                                 }
-
                             }
                             Err(e) => {
                                 ::ftlog::error!("recv signal error: {:?}", e);
                             }
                         }
-                    
+
                     }
-                    
+
                     _ => unreachable!()
                 }
             }
+            ::ftlog::info!("{} csv_reader thread exit", file_path.display());
+            dispatcher.broadcast_event(StreamItem::ReaderStopped);
         });
     }
 
 }
 
-fn retry_read_from_seek<T: for<'a> Deserialize<'a> + Send + Sync + Clone +'static>(file_path: &PathBuf, seek_pos: u64, enc_type: EncType, retry_times:i32) -> anyhow::Result<(u64, Vec<anyhow::Result<T>>, bool)>  {
-    let mut _read_success = false;
+/*
+LastRead: 读线程上次读取的位置(含行号)、最近读取的行及检查点
+*/
+struct LastRead<T> {
+    retention: Arc<RetentionBuffer<T>>, // 补发优先从内存读取
+    seek_line: (u64, u64), // (seek位置, 该位置之前的行数), 避免每次从头统计行号
+    checkpoint: ThrottledCheckpoint,
+}
+
+impl<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> LastRead<T> {
+    // 读取[begin, end)的数据, 内存未覆盖的部分从文件读取
+    fn read(&self, file_path: &PathBuf, begin: u64, end: u64, enc_type: EncType) -> anyhow::Result<Vec<RowData<T>>> {
+        self.retention.read(begin, end, |begin, end| read_csv_data_to_position::<T>(file_path, begin, end, enc_type))
+    }
+}
+
+// 从当前seek位置读到文件尾并分发; 分发成功后再保存检查点
+fn read_and_dispatch<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static>(
+    ctx: &ReadContext, // 本次读取的来源信息(含文件路径)
+    enc_type: EncType,
+    is_increment: bool,
+    seek_pos: &AtomicU64,
+    dispatcher: &MsgDispatcher<T>,
+    last_read: &mut LastRead<T>,
+    durable: &DurableCursors,
+) {
+    let file_path = ctx.file_path.as_ref();
+    let cur_seek_pos = seek_pos.load(Ordering::Relaxed); // 当前文件seek位置
+    let cur_seek_line = line_at(file_path, cur_seek_pos, last_read.seek_line);
+    let ctx = &ctx.clone().with_read_from(cur_seek_pos);
+    let chunk = match retry_read_from_seek::<T>(file_path, cur_seek_pos, cur_seek_line, enc_type, MAX_READ_RETRY_TIME) {
+        Ok(chunk) => chunk,
+        Err(e) => {
+            ::ftlog::error!("read error: {:?}", e);
+            return;
+        }
+    };
+    if is_increment { // 增量读; 需要更新POS
+        seek_pos.store(chunk.end_pos, Ordering::Relaxed);
+        last_read.seek_line = (chunk.end_pos, chunk.end_line);
+        last_read.retention.push(&chunk.rows, cur_seek_pos, chunk.end_pos, 1);
+    }
+    let data_len = chunk.rows.len();
+    ::ftlog::info!("read data len: {}", data_len);
+    let read_range = |begin, end| last_read.read(file_path, begin, end, enc_type);
+    let before_send = |routed: &[(CertKeyT, Vec<usize>)]| durable.before_send(&chunk.rows, chunk.end_pos, routed, dispatcher, ctx, read_range);
+    match dispatcher.dispatch_rows_with(&chunk.rows, ctx, before_send) { // 分发数据
+        Ok(_) => {
+            ::ftlog::debug!("dispatch success;len={}", data_len);
+            if is_increment {
+                last_read.checkpoint.save(chunk.end_pos);
+            }
+        }
+        Err(e) => {
+            ::ftlog::error!("dispatch error: {:?}", e);
+        }
+    }
+}
+
+// 读取到文件尾; 最后一行未写完时重试, 重试次数用尽后保留该行待下次读取
+fn retry_read_from_seek<T: for<'a> Deserialize<'a> + Send + Sync + Clone +'static>(file_path: &PathBuf, seek_pos: u64, seek_line: u64, enc_type: EncType, retry_times:i32) -> anyhow::Result<CsvChunk<T>>  {
     let mut retry_time = 0;
-    while !_read_success {
-        match read_csv_data::<T>(&file_path, seek_pos, enc_type) {
-            Ok((new_seek_pos, datas, is_read_success)) => {
-                _read_success = is_read_success;
-                if new_seek_pos == seek_pos ||
-                    datas.is_empty() || (!is_read_success && retry_time < retry_times) {
-                        retry_time += 1;
-                        if retry_time >= retry_times {
-                            break; // 跳出内层循环, 放弃本次读取
-                        }
-                        ::ftlog::info!("retry read csv file: {:?}", file_path.display());
-                        std::thread::sleep( std::time::Duration::from_millis(500));
-                        continue;
+    loop {
+        match read_csv_data::<T>(file_path, seek_pos, seek_line, enc_type) {
+            Ok(mut chunk) => {
+                if chunk.read_success {
+                    return Ok(chunk);
+                }
+                retry_time += 1;
+                if retry_time >= retry_times { // 放弃未写完的行, 下次从该行开始读取
+                    if let Some(partial) = chunk.rows.pop() {
+                        chunk.end_pos = partial.offset;
+                        chunk.end_line = partial.line - 1;
                     }
-                return Ok((new_seek_pos, datas, is_read_success));
-
+                    chunk.read_success = true;
+                    return Ok(chunk);
+                }
+                ::ftlog::info!("retry read csv file: {:?}", file_path.display());
             }
             Err(e) => {
-                ::ftlog::error!("read error: {:?}", e)
+                ::ftlog::error!("read error: {:?}", e);
+                retry_time += 1;
+                if retry_time >= retry_times {
+                    return Err(e);
+                }
             }
         }
+        std::thread::sleep( std::time::Duration::from_millis(500));
     }
-    Ok((0, vec![], false)) // 读取失败
 }
 
 impl<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> ReadRunner for SubsReader<T, CSV> {
     fn run(&self) {
         self.read_file_loop(); // 调用 CSV 版本的具体实现
     }
+
+    fn field_names(&self) -> anyhow::Result<csv::StringRecord> {
+        let headers = read_csv_headers(&self.file_path, self.enc_type);
+        if headers.is_empty() {
+            anyhow::bail!("{} csv header not found", self.file_path.display());
+        }
+        Ok(csv::StringRecord::from(headers))
+    }
 }
 
 // 从当前seek读取后续所有数据,并返回seek和数据,不改变记录的seek变量
-fn read_csv_data<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static>(file_path: &PathBuf, seek_pos: u64, enc_type: EncType) -> anyhow::Result<(u64, Vec<anyhow::Result<T>>, bool)> {
-    let ret_data : Vec<Result::<T>> = vec![];
-    let read_success = true;
-
+fn read_csv_data<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static>(file_path: &PathBuf, seek_pos: u64, seek_line: u64, enc_type: EncType) -> anyhow::Result<CsvChunk<T>> {
     let mut fd= File::open(file_path)?;
     fd.seek(SeekFrom::Start(seek_pos))?; // seek
 
-    let (read_size, data) = match enc_type { // 1. 读数据并转为string
-        EncType::GBK => {
-            let mut buf = vec![];
-            // 不能直接转gbk字节序列为utf8字节序列，会多增加一些字节，导致seek不对
-            let size = fd.read_to_end(&mut buf)? as u64;
-            let mut data = String::default();
-            DecodeReaderBytesBuilder::new().encoding(Some(encoding_rs::GBK)).build(&buf[..]).read_to_string(&mut data)?;
-            (size, data)
-        }
-        EncType::UTF8 => {
-            let mut buf = String::default();
-            let size = fd.read_to_string(&mut buf)? as u64;
-            (size, buf)
-        }
-    };
+    // 按原始字节解析, 不能先把gbk字节序列转为utf8, 会多增加一些字节，导致seek不对
+    let mut buf = vec![];
+    let read_size = fd.read_to_end(&mut buf)? as u64;
 
     if read_size == 0 { // 无数据变化
-        return Ok((seek_pos, ret_data, read_success))
+        return Ok(CsvChunk { end_pos: seek_pos, end_line: seek_line, rows: vec![], read_success: true })
     }
 
-    let new_pos = seek_pos + read_size; // 读取后的位置数据
-
-    match deserialize_from_str(&data, seek_pos == 0) {
-        Ok((data, read_success)) => {
-            Ok((new_pos, data, read_success))
-        }
-
-        Err(e) => {
-            ::ftlog::error!("{}", e);
-            Ok((new_pos, vec![], false))
-        }
-    }
+    let (rows, read_success) = parse_csv_rows::<T>(file_path, &buf, seek_pos, seek_line, enc_type);
+    Ok(CsvChunk {
+        end_pos: seek_pos + read_size, // 读取后的位置数据
+        end_line: seek_line + count_newlines(&buf),
+        rows,
+        read_success,
+    })
 }
 
-// 新增函数：从0位置读取到指定位置的数据
+// 读取[start_pos, end_pos)的数据
 fn read_csv_data_to_position<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static>(
-    file_path: &PathBuf, 
-    start_pos: u64, 
-    end_pos: u64, 
+    file_path: &PathBuf,
+    start_pos: u64,
+    end_pos: u64,
     enc_type: EncType
-) -> anyhow::Result<Vec<anyhow::Result<T>>> {    
+) -> anyhow::Result<Vec<RowData<T>>> {
+    let start_line = count_lines(file_path, start_pos)?;
     let mut fd = File::open(file_path)?;
     fd.seek(SeekFrom::Start(start_pos))?; // 从起始位置seek
-    
-    let read_size = end_pos - start_pos; // 计算需要读取的字节数
-    
-    let data = match enc_type { // 读取指定长度的数据并转为string
-        EncType::GBK => {
-            let mut buf = vec![0; read_size as usize];
-            let size = fd.read(&mut buf)? as u64;
-            let mut data = String::default();
-            DecodeReaderBytesBuilder::new()
-                .encoding(Some(encoding_rs::GBK))
-                .build(&buf[..size as usize])
-                .read_to_string(&mut data)?;
-            data
-        }
-        EncType::UTF8 => {
-            let mut buf = vec![0; read_size as usize];
-            let size = fd.read(&mut buf)? as u64;
-            String::from_utf8_lossy(&buf[..size as usize]).to_string()
-        }
-    };
 
-    if data.is_empty() { // 无数据变化
+    let mut buf = vec![];
+    fd.take(end_pos.saturating_sub(start_pos)).read_to_end(&mut buf)?; // 读取指定长度的数据
+
+    if buf.is_empty() { // 无数据变化
         return Ok(vec![]);
     }
 
-    match deserialize_from_str(&data, start_pos == 0) {
-        Ok((data, _read_success)) => {
-            Ok(data)
-        }
-        Err(e) => {
-            Ok(vec![Err(e)])
-        }
+    let (rows, _read_success) = parse_csv_rows::<T>(file_path, &buf, start_pos, start_line, enc_type);
+    Ok(rows)
+}
+
+// 统计[0, end_pos)内的换行数, 即end_pos之前的行数
+fn count_lines(file_path: &PathBuf, end_pos: u64) -> anyhow::Result<u64> {
+    if end_pos == 0 {
+        return Ok(0);
     }
+    let mut buf = vec![];
+    File::open(file_path)?.take(end_pos).read_to_end(&mut buf)?;
+    Ok(count_newlines(&buf))
 }
 
-// 从正确格式字符串中解析构建csv reader; 读数据
-fn deserialize_from_str<T: for<'a> Deserialize<'a> + Send + Sync + 'static>(data: &str, have_head: bool) -> anyhow::Result<(Vec<anyhow::Result<T>>, bool)> {
-    let mut ret_data : Vec<Result::<T>> = vec![];
-    let mut read_success = true;
-    let mut reader = csv::ReaderBuilder::new().flexible(true).has_headers(have_head).from_reader(data.trim().as_bytes());
-
-    for record in reader.records() {
-        match record {
-            Ok(record) =>  match record.deserialize::<T>(None) {
-                Ok(val) => {
-                    ret_data.push(Ok(val));
-                }
-                Err(e) => {
-                    ret_data.push(Err(anyhow::anyhow!(
-                        "Error decoding data: {:?}, error: {:?}",
-                        record,
-                        e
-                    )));
-                    read_success = false;
+// offset之后的第一个行首(offset恰为行首时即offset本身)
+fn next_line_start(file_path: &PathBuf, offset: u64) -> anyhow::Result<u64> {
+    if offset == 0 {
+        return Ok(0);
+    }
+    let mut fd = File::open(file_path)?;
+    fd.seek(SeekFrom::Start(offset - 1))?;
+    let mut skipped = vec![];
+    let len = BufReader::new(fd).read_until(b'\n', &mut skipped)?; // 包含offset前一个字节, 为换行符时不跳过
+    Ok(offset - 1 + len as u64)
+}
+
+fn count_newlines(buf: &[u8]) -> u64 {
+    buf.iter().filter(|b| **b == b'\n').count() as u64
+}
+
+// seek位置之前的行数: 位置未变化时使用缓存
+fn line_at(file_path: &PathBuf, seek_pos: u64, cache: (u64, u64)) -> u64 {
+    if cache.0 == seek_pos {
+        return cache.1;
+    }
+    count_lines(file_path, seek_pos).unwrap_or_else(|e| {
+        ::ftlog::error!("{} count lines error: {:?}", file_path.display(), e);
+        0
+    })
+}
+
+// 解析csv数据行; buf从文件base_offset处开始(base_offset为0时含表头), base_line为base_offset之前的行数
+// 按原始字节解析以保证GBK下偏移准确, 逐字段解码后反序列化
+fn parse_csv_rows<T: for<'a> Deserialize<'a> + Send + Sync + 'static>(file_path: &PathBuf, buf: &[u8], base_offset: u64, base_line: u64, enc_type: EncType) -> (Vec<RowData<T>>, bool) {
+    let mut ret_data = vec![];
+    let mut reader = csv::ReaderBuilder::new().flexible(true).has_headers(base_offset == 0).from_reader(buf);
+    let (mut scanned, mut newlines) = (0_usize, 0_u64); // 已统计换行的位置及换行数
+    let shared_path = OnceCell::new(); // 出错时才创建
+    let headers = OnceCell::new(); // 出错时才读取, 用于定位字段名
+    if base_offset == 0 && let Ok(header) = reader.byte_headers() {
+        let _ = headers.set(header.iter().map(|field| decode_lossy(field, enc_type)).collect::<Vec<_>>());
+    }
+    let field_name = |index: usize| -> Option<Arc<str>> {
+        headers.get_or_init(|| read_csv_headers(file_path, enc_type)).get(index).map(|name| Arc::from(name.as_str()))
+    };
+
+    let mut record = csv::ByteRecord::new();
+    loop {
+        match reader.read_byte_record(&mut record) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => {
+                ret_data.push(RowData {
+                    offset: base_offset + scanned as u64,
+                    line: base_line + newlines + 1,
+                    size: (reader.position().byte() as usize).saturating_sub(scanned),
+                    data: Err(ReadError::BadRecord {
+                        path: shared_path.get_or_init(|| Arc::new(file_path.clone())).clone(),
+                        offset: base_offset + scanned as u64,
+                        line: base_line + newlines + 1,
+                        source: Arc::new(e),
+                    }),
+                });
+                continue;
+            }
+        }
+        if record.len() == 1 && record[0].iter().all(|b| b.is_ascii_whitespace()) { // 空白行
+            continue;
+        }
+        let mut row_pos = record.position().map(|p| p.byte() as usize).unwrap_or(0);
+        while buf.get(row_pos).is_some_and(|b| *b == b'\r' || *b == b'\n') { // 位置可能落在上一行的换行符上
+            row_pos += 1;
+        }
+        if row_pos > scanned {
+            newlines += count_newlines(&buf[scanned..row_pos]);
+            scanned = row_pos;
+        }
+        let (offset, line) = (base_offset + row_pos as u64, base_line + newlines + 1);
+        let size = (reader.position().byte() as usize).saturating_sub(row_pos);
+        let raw_text = || -> Arc<str> {
+            let end = (reader.position().byte() as usize).clamp(row_pos, buf.len());
+            Arc::from(decode_lossy(&buf[row_pos..end], enc_type).trim_end_matches(['\r', '\n']))
+        };
+
+        let mut fields = Vec::with_capacity(record.len());
+        let mut decode_err = None;
+        for (index, field) in record.iter().enumerate() {
+            match decode_field(field, enc_type) {
+                Some(field) => fields.push(field),
+                None => {
+                    decode_err = Some(index);
+                    break;
                 }
             }
+        }
+        if let Some(index) = decode_err {
+            ret_data.push(RowData { offset, line, size, data: Err(ReadError::Decode {
+                path: shared_path.get_or_init(|| Arc::new(file_path.clone())).clone(),
+                offset,
+                line,
+                raw: raw_text(),
+                field: field_name(index),
+            })});
+            continue;
+        }
+
+        let string_record = csv::StringRecord::from(fields);
+        let data = match string_record.deserialize::<T>(None) {
+            Ok(val) => Ok(val),
             Err(e) => {
-                ret_data.push(Err(e.into()));
+                let field = match e.kind() {
+                    csv::ErrorKind::Deserialize { err, .. } => err.field().and_then(|index| field_name(index as usize)),
+                    _ => None,
+                };
+                Err(ReadError::Deserialize {
+                    path: shared_path.get_or_init(|| Arc::new(file_path.clone())).clone(),
+                    offset,
+                    line,
+                    raw: raw_text(),
+                    field,
+                    source: Arc::new(e),
+                })
             }
+        };
+        ret_data.push(RowData { offset, line, size, data });
+    }
+    // 最后一行出错且没有换行符, 可能是行未写完(或多字节字符被截断), 需要重读; 完整的错误行照常分发
+    let read_success = buf.ends_with(b"\n") || ret_data.last().is_none_or(|row| row.data.is_ok());
+    (ret_data, read_success)
+}
 
+// 按文件编码解码字段, 存在非法字节时返回None
+fn decode_field(field: &[u8], enc_type: EncType) -> Option<String> {
+    match enc_type {
+        EncType::GBK => {
+            let (text, had_errors) = encoding_rs::GBK.decode_without_bom_handling(field);
+            (!had_errors).then(|| text.into_owned())
         }
+        EncType::UTF8 => std::str::from_utf8(field).ok().map(|text| text.to_string()),
+    }
+}
+
+// 按文件编码解码, 非法字节替换为U+FFFD
+pub(crate) fn decode_lossy(bytes: &[u8], enc_type: EncType) -> String {
+    match enc_type {
+        EncType::GBK => encoding_rs::GBK.decode_without_bom_handling(bytes).0.into_owned(),
+        EncType::UTF8 => String::from_utf8_lossy(bytes).into_owned(),
     }
-    Ok((ret_data, read_success))
 }
 
+// 读取表头字段名
+fn read_csv_headers(file_path: &PathBuf, enc_type: EncType) -> Vec<String> {
+    match csv::ReaderBuilder::new().flexible(true).from_path(file_path) {
+        Ok(mut reader) => match reader.byte_headers() {
+            Ok(header) => header.iter().map(|field| decode_lossy(field, enc_type)).collect(),
+            Err(_) => vec![],
+        },
+        Err(_) => vec![],
+    }
+}
 
-fn read_to_seek<T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Clone>(file_path: &PathBuf, seek_pos: u64, enc_type: EncType) -> anyhow::Result<Vec<anyhow::Result<T>>> {
-    // 调用 CSV 读取逻辑
-    read_csv_data_to_position::<T>(file_path, 0, seek_pos, enc_type)
-}
\ No newline at end of file
+// 回放: 读到end_pos(None为文件尾); 按字节偏移时从偏移之后的行首开始读取, 按行序号/时间时从头读取后跳过起点之前的行
+fn read_csv_replay<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static>(
+    file_path: &PathBuf,
+    from: &ReplayFrom<T>,
+    end_pos: Option<u64>,
+    enc_type: EncType,
+) -> anyhow::Result<Vec<RowData<T>>> {
+    let end_pos = end_pos.unwrap_or(u64::MAX);
+    if let ReplayFrom::ByteOffset(offset) = from { // 从偏移处读取, 不重新解析之前的数据
+        return read_csv_data_to_position::<T>(file_path, next_line_start(file_path, *offset)?, end_pos, enc_type);
+    }
+    let mut rows = read_csv_data_to_position::<T>(file_path, 0, end_pos, enc_type)?;
+    let first = match from {
+        ReplayFrom::ByteOffset(_) => Some(0), // 已按偏移读取
+        ReplayFrom::Row(row) => Some(*row as usize),
+        ReplayFrom::Time { time_of, since } => rows.iter().position(|row| matches!(&row.data, Ok(data) if time_of(data) >= *since)),
+    };
+    Ok(rows.split_off(first.unwrap_or(rows.len()).min(rows.len())))
+}
@@ -1,8 +1,95 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
 use anyhow::bail;
-use super::subscribe_reader::{ReadRunner};
-use crate::common::model::{DBF, NotifyEvent, READ_FROM_HEAD_FLAG};
+use super::subscribe_reader::{ReadRunner, ReadRequest, ReplayFrom, send_snapshot, stop_if_no_subscriber, linger_notify};
+use super::msg_dispatcher::{MsgDispatcher, CertKeyT};
+use super::checkpoint::ThrottledCheckpoint;
+use super::durable::DurableCursors;
+use super::envelope::{ReadContext, RowData};
+use super::error::ReadError;
+use super::csv_reader::decode_lossy;
+use super::stream::{HeartbeatTimer, StreamItem};
+use super::retention::RetentionBuffer;
+use crate::common::timer::get_coarse_timestamp_ms;
+use crate::common::model::{DBF, EncType, FileIdentity, NotifyEvent, MAX_READ_RETRY_TIME, DBF_STABLE_CHECK_INTERVAL};
 use crate::reader::subscribe_reader::SubsReader;
+use crate::writer::{is_self_write, self_write_generation};
+use crate::writer::dbf_writer::{update_dbf_record, FieldValue};
+
+const DBF_HEADER_PREFIX_LEN: usize = 12; // 版本(1)+日期(3)+记录数(4)+头长度(2)+记录长度(2)
+const DBF_FILE_TERMINATOR_LEN: u64 = 1; // 0x1A 文件结束符
+const DBF_DELETED_FLAG: u8 = b'*'; // 记录删除标记
+
+/*
+DbfFileState: DBF文件头与文件长度快照, 用于判断文件是否被整体重写/处于写入中
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DbfFileState {
+    pub file_len: u64, // 文件实际长度
+    pub num_records: u64, // 文件头记录数
+    pub header_len: u64, // 文件头长度(第一条记录偏移)
+    pub record_len: u64, // 单条记录长度
+    pub modified: Option<SystemTime>, // 最后修改时间
+}
+
+impl DbfFileState {
+    /// 读取文件头快照
+    pub fn load(file_path: &PathBuf) -> anyhow::Result<Self> {
+        let mut fd = File::open(file_path)?;
+        Self::read_from(&mut fd)
+    }
+
+    /// 从已打开的文件读取文件头快照(读取后文件位置不确定)
+    pub fn read_from(fd: &mut File) -> anyhow::Result<Self> {
+        fd.seek(SeekFrom::Start(0))?;
+        let meta = fd.metadata()?;
+        let mut buf = [0u8; DBF_HEADER_PREFIX_LEN];
+        fd.read_exact(&mut buf)?; // 文件被清空时返回错误
+        Ok(Self {
+            file_len: meta.len(),
+            num_records: u32::from_le_bytes([buf[4], buf[5], buf[6], buf[7]]) as u64,
+            header_len: u16::from_le_bytes([buf[8], buf[9]]) as u64,
+            record_len: u16::from_le_bytes([buf[10], buf[11]]) as u64,
+            modified: meta.modified().ok(),
+        })
+    }
+
+    /// 文件头记录数是否与文件长度一致(允许末尾有/无结束符)
+    pub fn is_consistent(&self) -> bool {
+        if self.header_len == 0 || self.record_len == 0 {
+            return false;
+        }
+        let expect_len = self.header_len + self.num_records * self.record_len;
+        self.file_len == expect_len || self.file_len == expect_len + DBF_FILE_TERMINATOR_LEN
+    }
+}
+
+/// 等待DBF文件处于一致且稳定的状态; 重试次数用尽仍不稳定则返回None
+///
+/// 部分写入方(如`src/test/write_dbf.py`)每次追加都会重建整个文件,
+/// 期间文件可能为空或比之前短, 此时不能据此重置读取位置.
+/// 文件头一致且与上次读取时(last)相同则直接返回, 否则每次间隔后重新检查, 连续两次相同才视为稳定
+pub fn wait_dbf_stable(file_path: &PathBuf, last: Option<&DbfFileState>, retry_times: i32) -> Option<DbfFileState> {
+    let mut prev_state = None;
+    for retry_time in 0..retry_times {
+        match DbfFileState::load(file_path) {
+            Ok(state) if state.is_consistent() => {
+                if last == Some(&state) || prev_state == Some(state) {
+                    return Some(state);
+                }
+                prev_state = Some(state);
+            }
+            _ => prev_state = None,
+        }
+        ::ftlog::debug!("{} not stable, retry={}", file_path.display(), retry_time);
+        std::thread::sleep(DBF_STABLE_CHECK_INTERVAL);
+    }
+    None
+}
 
 impl <T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Clone> SubsReader<T, DBF> {
     pub fn read_file_loop(&self) {
@@ -10,120 +97,129 @@ impl <T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Clone> SubsR
         let file_path = self.file_path.clone();
         let is_increment = self.is_increment;
         let seek_pos = self.seek_pos.clone();
-        let _enc_type = self.enc_type;
+        let enc_type = self.enc_type;
         let dispatcher = self.msg_dispatcher.clone();
+        let checkpoint = self.checkpoint.clone();
+        let durable = self.durable.clone();
+        let retention = self.retention.clone();
+        let heartbeat_interval = self.options.heartbeat_interval;
 
         let recv_notify_signal_chan = self.notify_meta.receiver.clone();
         let recv_read_signal_chan = self.inner_chan.1.clone();
 
         std::thread::spawn(move || {
-            let mut _read_success = true; // 上次read是否成功
-            let mut _last_read_size = 0; // 上次读取字节数
-            let mut _last_read_time = 0_u64; // 上次读取时间 (避免read间隔太频繁)
-
+            let shared_path = Arc::new(file_path.clone()); // 填入Envelope
+            let mut checkpoint = ThrottledCheckpoint::new(checkpoint);
+            let mut last_read = LastRead {
+                retention,
+                state: None,
+                identity: FileIdentity::of(&file_path).unwrap_or_default(),
+                fields: read_dbf_field_names(&file_path),
+                self_write_gen: self_write_generation(),
+                enc_type,
+            };
+            let replay = |cert_key: CertKeyT, from: &ReplayFrom<T>| { // 从回放起点读到当前位置, 仅发给该订阅者
+                let end_pos = if is_increment { Some(seek_pos.load(Ordering::Acquire)) } else { None };
+                match read_dbf_replay::<T>(&file_path, from, end_pos, enc_type) {
+                    Ok(rows) => {
+                        ::ftlog::info!("{} replay data len: {}", file_path.display(), rows.len());
+                        let ctx = ReadContext::now(&shared_path);
+                        let ctx = match rows.first() { // 回放的行从文件读取, 第一行的偏移即读取起点
+                            Some(row) => ctx.with_read_from(row.offset),
+                            None => ctx,
+                        };
+                        if let Err(e) = dispatcher.send_to(&rows, &ctx, cert_key) {
+                            ::ftlog::error!("replay send error: {:?}", e);
+                        }
+                    }
+                    Err(e) => {
+                        ::ftlog::error!("replay read error: {:?}", e);
+                    }
+                }
+            };
+            let mut heartbeat = HeartbeatTimer::new(heartbeat_interval);
             let mut selector = crossbeam::channel::Select::new();
             let notify_idx = selector.recv(&recv_notify_signal_chan);
             let read_idx = selector.recv(&recv_read_signal_chan);
 
-            while is_running.load(std::sync::atomic::Ordering::Relaxed) {
-                
-                let select_idx = selector.select();
+            while is_running.load(Ordering::Relaxed) {
+                if stop_if_no_subscriber(&dispatcher, &is_running) { // 接收端全部关闭
+                    break;
+                }
+
+                let timeout = heartbeat.timeout().into_iter().chain(checkpoint.timeout()).min(); // 心跳或延后保存的位置到期
+                let select_idx = match timeout {
+                    Some(timeout) => selector.select_timeout(timeout).ok(),
+                    None => Some(selector.select()),
+                };
+                checkpoint.flush_due();
+                if heartbeat.is_due() {
+                    dispatcher.broadcast_event(StreamItem::Heartbeat { time: get_coarse_timestamp_ms(), pos: seek_pos.load(Ordering::Acquire) });
+                }
+                let Some(select_idx) = select_idx else {
+                    continue; // 等待超时
+                };
                 match select_idx.index() {
                     i if i == notify_idx => {
                         // 文件在监听之前有数据,这种情况处理在其他地方完成
+                        let mut notify_time = get_coarse_timestamp_ms();
                         match select_idx.recv(&recv_notify_signal_chan) { // 阻塞等待事件
                                 Ok(event_data) => {
                                     match event_data.event {
                                         NotifyEvent::ScheduleEvent | NotifyEvent::WriteEvent => {
                                             // 触发读取逻辑
+                                            notify_time = event_data.last_notify_time;
                                         }
                                         NotifyEvent::StopEvent => {
-                                            break;
+                                            continue;
                                         }
                                     }
                                 }
                                 Err(e) => {
-                                    println!("recv signal error: {:?}", e);
+                                    ::ftlog::error!("recv signal error: {:?}", e);
                                 }
                             }
 
+                        linger_notify(dispatcher.batch_limits().linger, &recv_notify_signal_chan);
                         ::ftlog::trace!("ready reading file: {}", file_path.display());
-                        
-                        let begin_seek = if is_increment {
-                            seek_pos.load(std::sync::atomic::Ordering::Acquire)
-                        } else {
-                            0
-                        };
-                        match read_from_seek::<T>(&file_path, begin_seek as _) {
-                            Ok(data) => {
-                                let length = data.len();
-                                if is_increment {
-                                    seek_pos.fetch_add(length as _, std::sync::atomic::Ordering::Acquire);
-                                }
-                                if length > 0 {
-                                    match dispatcher.dispatch(data) {
-                                        Ok(_) => {
-                                            println!("send success")
-                                        },
-                                        Err(e) => {
-                                            println!("send data error: {:?}", e)
-                                        }
-                                    }
-                                }
-
-                            }
-                            Err(e) => {
-                                println!("{:?}", e);
-                            }
-                        }
-                    
+                        let ctx = ReadContext::new(&shared_path, notify_time);
+                        read_and_dispatch(&ctx, is_increment, &seek_pos, &dispatcher, &mut last_read, &mut checkpoint, &durable);
                     },
                     i if i == read_idx => {
                         match select_idx.recv(&recv_read_signal_chan) { // 接受该事件
-                            Ok((cert_key, _seek_pos)) => {
-                                if _seek_pos == READ_FROM_HEAD_FLAG { // 从0到文件尾部
-                                    let begin_seek = if is_increment {
-                                        seek_pos.load(std::sync::atomic::Ordering::Acquire)
-                                    } else {
-                                        0
-                                    };
-                                    match read_from_seek::<T>(&file_path, begin_seek as _) {
-                                        Ok(data) => { // read 成功
-                                            let length = data.len();
-                                            if is_increment {
-                                                seek_pos.fetch_add(length as _, std::sync::atomic::Ordering::Acquire);
-                                            }
-                                            if length > 0 {
-                                                match dispatcher.dispatch(data) {
-                                                    Ok(_) => {
-                                                        ::ftlog::info!("{} read {} data success", file_path.display(), length)
-                                                    },
-                                                    Err(e) => {
-                                                        ::ftlog::error!("send data error: {:?}", e)
-                                                    }
-                                                }
-                                            }
-
-                                        }
-                                        Err(e) => {
-                                            ::ftlog::error!("{:?}", e);
-                                            #[cfg(feature = "reset_seek_when_err")] {
-                                                ::ftlog::info!("{} retry read error. reset seek pos", file_path.display());
-                                                seek_pos.store(0, std::sync::atomic::Ordering::Release);
-                                            }
+                            Ok(request) => match request {
+                                ReadRequest::FromHead => { // 从0到文件尾部
+                                    read_and_dispatch(&ReadContext::now(&shared_path), is_increment, &seek_pos, &dispatcher, &mut last_read, &mut checkpoint, &durable);
+                                }
+                                ReadRequest::DurableActivate => { // 具名订阅补发
+                                    durable.catch_up(seek_pos.load(Ordering::Acquire), &dispatcher, &ReadContext::now(&shared_path), |begin, end| last_read.read(&file_path, begin, end));
+                                }
+                                ReadRequest::Replay(cert_key, from) => {
+                                    replay(cert_key, &from);
+                                }
+                                ReadRequest::Resume(cert_key) => { // 先补发暂停期间的数据, 之后接收实时数据
+                                    if let Some(offset) = dispatcher.resume(cert_key, &ReadContext::now(&shared_path)) {
+                                        match DbfFileState::load(&file_path) { // 暂停位置为记录序号, 按文件头换算为字节偏移
+                                            Ok(state) => replay(cert_key, &ReplayFrom::ByteOffset(state.header_len + offset * state.record_len)),
+                                            Err(e) => ::ftlog::error!("{} resume read header error: {:?}", file_path.display(), e),
                                         }
                                     }
-                    
-                                } else {
-                                    let from_zero_data = read_to_seek::<T>(&file_path, _seek_pos);
+                                }
+                                ReadRequest::ToPos(cert_key, _seek_pos) => {
+                                    let from_zero_data = last_read.read(&file_path, 0, _seek_pos);
                                     if from_zero_data.is_err() {
                                         // 读失败则不尝试重试
                                         continue;
                                     }
                                     let from_zero_data = from_zero_data.unwrap(); // [safe] not err
-                                    let _= dispatcher.dispatch_single(from_zero_data, cert_key);
+                                    let _= dispatcher.dispatch_single(&from_zero_data, &ReadContext::now(&shared_path).with_read_from(0), cert_key);
+                                }
+                                ReadRequest::CaughtUp(cert_key) => {
+                                    dispatcher.send_event(cert_key, StreamItem::CaughtUp { pos: seek_pos.load(Ordering::Acquire) });
+                                }
+                                ReadRequest::Snapshot(cert_key) => { // 读线程是唯一推进seek的地方, 快照结束位置即实时数据起点
+                                    send_snapshot(&dispatcher, cert_key, last_read.read(&file_path, 0, seek_pos.load(Ordering::Acquire)), &ReadContext::now(&shared_path));
                                 }
-
                             }
                             Err(e) => {
                                 ::ftlog::error!("recv signal error: {:?}", e);
@@ -134,6 +230,8 @@ impl <T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Clone> SubsR
                 }
 
             }
+            ::ftlog::info!("{} dbf_reader thread exit", file_path.display());
+            dispatcher.broadcast_event(StreamItem::ReaderStopped);
         });
     }
 }
@@ -142,40 +240,188 @@ impl<T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Clone> ReadRu
     fn run(&self) {
         self.read_file_loop(); // 调用 DBF 版本的具体实现
     }
+
+    fn field_names(&self) -> anyhow::Result<csv::StringRecord> {
+        let reader = dbase::Reader::from_path(&self.file_path).map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(reader.fields().iter().map(|field| field.name()).collect())
+    }
 }
 
-pub fn read_to_seek<T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Clone>(file_path:&PathBuf, seek_pos: u64) -> anyhow::Result<Vec<anyhow::Result<T>>> {
-    // 调用 DBF 读取逻辑
+/*
+LastRead: 读线程上次处理时的文件状态, 用于识别自身写回、文件替换和字段变化; 以及最近读取的行
+*/
+struct LastRead<T> {
+    retention: Arc<RetentionBuffer<T>>, // 补发优先从内存读取
+    state: Option<DbfFileState>, // 上次处理的文件状态
+    identity: FileIdentity,
+    fields: Vec<String>,
+    self_write_gen: u64, // 已处理的自身写回代数
+    enc_type: EncType, // 错误行原始内容的编码
+}
+
+impl<T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Clone> LastRead<T> {
+    // 读取[begin, end)的记录, 内存未覆盖的部分从文件读取
+    fn read(&self, file_path: &PathBuf, begin: u64, end: u64) -> anyhow::Result<Vec<RowData<T>>> {
+        self.retention.read(begin, end, |begin, end| read_range::<T>(file_path, begin, end, self.enc_type))
+    }
+}
+
+// 字段名列表, 读取失败时为空
+fn read_dbf_field_names(file_path: &PathBuf) -> Vec<String> {
     match dbase::Reader::from_path(file_path) {
-        Ok(mut reader) => {
-            let _ = reader.seek(0);
-            let res = reader
-                .iter_records_as::<T>()
-                .take(seek_pos as _) // 读取前N条
-                .map(|e| match e {
-                    Ok(r) => Ok(r),
-                    Err(e) => Err(anyhow::anyhow!("{:?}", e)),
-                })
-                .collect::<Vec<_>>();
+        Ok(reader) => reader.fields().iter().map(|field| field.name().to_string()).collect(),
+        Err(_) => vec![],
+    }
+}
+
+// 等待文件稳定后, 从上次分发的记录序号继续读取并分发
+fn read_and_dispatch<T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Clone>(
+    ctx: &ReadContext, // 本次读取的来源信息(含文件路径)
+    is_increment: bool,
+    seek_pos: &Arc<AtomicU64>,
+    dispatcher: &Arc<MsgDispatcher<T>>,
+    last_read: &mut LastRead<T>,
+    checkpoint: &mut ThrottledCheckpoint,
+    durable: &DurableCursors,
+) {
+    let file_path = ctx.file_path.as_ref();
+    let state = match wait_dbf_stable(file_path, last_read.state.as_ref(), MAX_READ_RETRY_TIME) {
+        Some(state) => state,
+        None => { // 文件正在被重写; 等待下一次通知, 不重置位置
+            ::ftlog::info!("{} is being rewritten, wait next notify", file_path.display());
+            return;
+        }
+    };
+    let last_state = last_read.state.replace(state);
+    if let Some(last) = last_state && is_self_write(file_path, &last, &state, &mut last_read.self_write_gen) {
+        ::ftlog::debug!("{} changed by self write back, skip", file_path.display());
+        return;
+    }
+    let identity = FileIdentity::of(file_path).unwrap_or_default();
+    let old_identity = std::mem::replace(&mut last_read.identity, identity);
+
+    let mut begin_seek = if is_increment {
+        seek_pos.load(Ordering::Acquire)
+    } else {
+        0
+    };
+    if state.num_records < begin_seek { // 文件稳定且记录数变少: 确实被截断或替换
+        ::ftlog::info!("{} records shrink from {} to {}", file_path.display(), begin_seek, state.num_records);
+        if !cfg!(feature = "reset_seek_when_err") {
+            return;
+        }
+        ::ftlog::info!("{} reset seek pos", file_path.display());
+        seek_pos.store(0, Ordering::Release);
+        checkpoint.save_now(0);
+        durable.on_reset();
+        dispatcher.on_reset();
+        last_read.retention.clear(0);
+        begin_seek = 0;
+        dispatcher.broadcast_event(if old_identity != FileIdentity::default() && old_identity != identity {
+            StreamItem::FileRotated { old: old_identity, new: identity }
+        } else {
+            StreamItem::FileReset { identity }
+        });
+    }
+    if last_state.is_none_or(|last| last.header_len != state.header_len || last.record_len != state.record_len) {
+        let fields = read_dbf_field_names(file_path);
+        if fields != last_read.fields {
+            dispatcher.broadcast_event(StreamItem::SchemaChanged { fields: fields.clone() });
+            last_read.fields = fields;
+        }
+    }
+    if state.num_records == begin_seek {
+        return;
+    }
 
-            return Ok(res);
+    let ctx = &ctx.clone().with_read_from(begin_seek);
+    match read_range::<T>(file_path, begin_seek, state.num_records, last_read.enc_type) {
+        Ok(rows) => {
+            if is_increment {
+                seek_pos.store(state.num_records, Ordering::Release); // 记录已分发的记录序号
+                last_read.retention.push(&rows, begin_seek, state.num_records, state.record_len);
+            }
+            let length = rows.len();
+            let read = |begin, end| last_read.read(file_path, begin, end);
+            if length > 0 {
+                let before_send = |routed: &[(CertKeyT, Vec<usize>)]| if is_increment {
+                    durable.before_send(&rows, state.num_records, routed, dispatcher, ctx, read);
+                };
+                match dispatcher.dispatch_rows_with(&rows, ctx, before_send) {
+                    Ok(_) => {
+                        ::ftlog::info!("{} read {} data success", file_path.display(), length);
+                        if is_increment {
+                            checkpoint.save(state.num_records); // 分发成功后再保存
+                        }
+                    },
+                    Err(e) => {
+                        ::ftlog::error!("send data error: {:?}", e)
+                    }
+                }
+            } else if is_increment {
+                durable.before_send(&rows, state.num_records, &[], dispatcher, ctx, read);
+                checkpoint.save(state.num_records);
+            }
         }
         Err(e) => {
-            bail!("{}", e)
+            ::ftlog::error!("{:?}", e);
         }
     }
 }
 
-fn read_from_seek<T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Clone>(file_path:&PathBuf, begin_seek: u64) -> anyhow::Result<Vec<anyhow::Result<T>>> {
-    match dbase::Reader::from_path(&file_path) {
+impl <T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Clone> SubsReader<T, DBF> {
+    /// 原地更新第index条记录的指定字段(如回写处理状态), 见[`update_dbf_record`]
+    pub fn update_record(&self, index: u64, fields: &[(&str, FieldValue)]) -> anyhow::Result<()> {
+        update_dbf_record(&self.file_path, index, fields)
+    }
+}
+
+pub fn read_to_seek<T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Clone>(file_path:&PathBuf, seek_pos: u64, enc_type: EncType) -> anyhow::Result<Vec<RowData<T>>> {
+    // 调用 DBF 读取逻辑
+    read_range::<T>(file_path, 0, seek_pos, enc_type)
+}
+
+// 回放: 从回放起点对应的记录读到end_seek(None为文件头记录数)
+fn read_dbf_replay<T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Clone>(file_path: &PathBuf, from: &ReplayFrom<T>, end_seek: Option<u64>, enc_type: EncType) -> anyhow::Result<Vec<RowData<T>>> {
+    let state = DbfFileState::load(file_path)?;
+    let end_seek = end_seek.unwrap_or(state.num_records).min(state.num_records);
+    let begin_seek = match from {
+        ReplayFrom::ByteOffset(offset) => {
+            if state.record_len == 0 {
+                bail!("{} invalid record length", file_path.display());
+            }
+            offset.saturating_sub(state.header_len).div_ceil(state.record_len)
+        }
+        ReplayFrom::Row(row) => { // 第row条未删除的记录
+            let live = scan_live_records(file_path, 0, end_seek)?;
+            live.get(*row as usize).map_or(end_seek, |(index, _)| *index)
+        }
+        ReplayFrom::Time { time_of, since } => {
+            let mut rows = read_range::<T>(file_path, 0, end_seek, enc_type)?;
+            let first = rows.iter().position(|row| matches!(&row.data, Ok(d) if time_of(d) >= *since)).unwrap_or(rows.len());
+            return Ok(rows.split_off(first));
+        }
+    };
+    read_range::<T>(file_path, begin_seek, end_seek, enc_type)
+}
+
+// 读取[begin_seek, end_seek)区间的记录, 跳过已删除的记录
+fn read_range<T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Clone>(file_path:&PathBuf, begin_seek: u64, end_seek: u64, enc_type: EncType) -> anyhow::Result<Vec<RowData<T>>> {
+    let records = scan_live_records(file_path, begin_seek, end_seek)?;
+    let shared_path = Arc::new(file_path.clone());
+    match dbase::Reader::from_path(file_path) {
         Ok(mut reader) => {
             let _= reader.seek(begin_seek as _);
+            let field_lens = reader.fields().iter().map(|field| field.length() as usize).collect::<Vec<_>>();
 
             let res = reader
                 .iter_records_as::<T>()
-                .map(|e| match e {
-                    Ok(r) => Ok(r),
-                    Err(e) => Err(anyhow::anyhow!("{:?}", e)),
+                .zip(records)
+                .map(|(e, (index, raw))| RowData {
+                    offset: index,
+                    line: index + 1,
+                    size: raw.len() + 1, // 含删除标记
+                    data: e.map_err(|e| to_read_error(e, &shared_path, index, &raw_text(&raw, &field_lens, enc_type))),
                 })
                 .collect::<Vec<_>>();
             Ok(res)
@@ -184,4 +430,66 @@ fn read_from_seek<T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static +
             bail!("{}", e)
         }
     }
-}
\ No newline at end of file
+}
+
+// [begin_seek, end_seek)区间内未删除记录的序号及原始字节(dbase遍历时会跳过已删除记录)
+fn scan_live_records(file_path: &PathBuf, begin_seek: u64, end_seek: u64) -> anyhow::Result<Vec<(u64, Vec<u8>)>> {
+    let mut fd = File::open(file_path)?;
+    let state = DbfFileState::read_from(&mut fd)?;
+    let end_seek = end_seek.min(state.num_records);
+    if begin_seek >= end_seek {
+        return Ok(vec![]);
+    }
+    let mut buf = vec![];
+    fd.seek(SeekFrom::Start(state.header_len + begin_seek * state.record_len))?;
+    fd.take((end_seek - begin_seek) * state.record_len).read_to_end(&mut buf)?;
+    Ok(buf.chunks(state.record_len as usize)
+        .zip(begin_seek..end_seek)
+        .filter(|(record, _)| record[0] != DBF_DELETED_FLAG)
+        .map(|(record, index)| (index, record[1..].to_vec()))
+        .collect())
+}
+
+// 原始记录按字段拆分为CSV行(与隔离文件格式一致), 按文件编码解码
+fn raw_text(raw: &[u8], field_lens: &[usize], enc_type: EncType) -> String {
+    let mut fields = vec![];
+    let mut pos = 0;
+    for len in field_lens {
+        let end = (pos + len).min(raw.len());
+        fields.push(decode_lossy(&raw[pos..end], enc_type).trim().to_string());
+        pos = end;
+    }
+    let mut writer = csv::WriterBuilder::new().terminator(csv::Terminator::Any(b'\n')).from_writer(vec![]);
+    let _ = writer.write_record(&fields);
+    let line = writer.into_inner().unwrap_or_default();
+    String::from_utf8_lossy(&line).trim_end().to_string()
+}
+
+// dbase错误转换为ReadError
+fn to_read_error(e: dbase::Error, path: &Arc<PathBuf>, index: u64, raw: &str) -> ReadError {
+    let field = e.field().as_ref().map(|field| Arc::from(field.name()));
+    let raw: Arc<str> = Arc::from(raw);
+    match e.kind() {
+        dbase::ErrorKind::IoError(io_err) => ReadError::Io {
+            path: path.clone(),
+            offset: index,
+            line: index + 1,
+            source: Arc::new(std::io::Error::new(io_err.kind(), e.to_string())),
+        },
+        dbase::ErrorKind::StringDecodeError(_) => ReadError::Decode {
+            path: path.clone(),
+            offset: index,
+            line: index + 1,
+            raw,
+            field,
+        },
+        _ => ReadError::Deserialize {
+            path: path.clone(),
+            offset: index,
+            line: index + 1,
+            raw,
+            field,
+            source: Arc::new(e),
+        },
+    }
+}
@@ -0,0 +1,145 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use anyhow::Result;
+use crossbeam::channel::{bounded, Receiver, Sender, TrySendError};
+use serde::{Deserialize, Serialize};
+use crate::common::model::EncType;
+use crate::common::timer::get_coarse_timestamp_ms;
+use crate::writer::csv_writer::CsvWriter;
+use super::envelope::RowData;
+use super::error::ReadError;
+
+/*
+QuarantineRecord: 隔离文件中的一行(UTF8 CSV), 运维修正RAW后可通过redeliver重新分发
+RAW为CSV格式的原始记录(DBF为按字段拆分后的值)
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    #[serde(rename = "TIME")]
+    pub time: u64, // 隔离时间(ms)
+    #[serde(rename = "FILE_PATH")]
+    pub file_path: String,
+    #[serde(rename = "OFFSET")]
+    pub offset: u64,
+    #[serde(rename = "LINE")]
+    pub line: u64,
+    #[serde(rename = "KIND")]
+    pub kind: String, // Io/Decode/BadRecord/Deserialize/Other
+    #[serde(rename = "FIELD")]
+    pub field: String,
+    #[serde(rename = "ERROR")]
+    pub error: String,
+    #[serde(rename = "RAW")]
+    pub raw: String,
+}
+
+impl QuarantineRecord {
+    pub fn from_error(e: &ReadError) -> Self {
+        let kind = match e {
+            ReadError::Io { .. } => "Io",
+            ReadError::Decode { .. } => "Decode",
+            ReadError::BadRecord { .. } => "BadRecord",
+            ReadError::Deserialize { .. } => "Deserialize",
+            ReadError::Other(_) => "Other",
+        };
+        Self {
+            time: get_coarse_timestamp_ms(),
+            file_path: e.path().map(|p| p.display().to_string()).unwrap_or_default(),
+            offset: e.offset().unwrap_or(0),
+            line: e.line().unwrap_or(0),
+            kind: kind.to_string(),
+            field: e.field().unwrap_or_default().to_string(),
+            error: e.to_string(),
+            raw: e.raw().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+/*
+DeadLetter: 错误行的去处(死信通道和/或隔离文件), 配置后错误行不再发给普通订阅者
+*/
+pub struct DeadLetter {
+    channel: Option<(Sender<ReadError>, Receiver<ReadError>)>, // 死信通道, 满时丢弃(隔离文件中仍有记录)
+    quarantine: Option<CsvWriter<QuarantineRecord>>, // 隔离文件
+}
+
+impl DeadLetter {
+    pub fn new(channel_capacity: Option<usize>, quarantine_file: Option<PathBuf>) -> Self {
+        Self {
+            channel: channel_capacity.map(bounded),
+            quarantine: quarantine_file.map(|path| CsvWriter::new(path, EncType::UTF8)),
+        }
+    }
+
+    /// 死信接收端
+    pub fn receiver(&self) -> Option<Receiver<ReadError>> {
+        self.channel.as_ref().map(|(_, receiver)| receiver.clone())
+    }
+
+    /// 隔离文件路径
+    pub fn quarantine_file(&self) -> Option<&PathBuf> {
+        self.quarantine.as_ref().map(|writer| &writer.file_path)
+    }
+
+    /// 投递一批错误行
+    pub fn push(&self, errors: &[&ReadError]) {
+        if errors.is_empty() {
+            return;
+        }
+        if let Some(writer) = &self.quarantine {
+            let records = errors.iter().map(|e| QuarantineRecord::from_error(e)).collect::<Vec<_>>();
+            if let Err(e) = writer.append_all(&records) {
+                ::ftlog::error!("{} write quarantine error: {:?}", writer.file_path.display(), e);
+            }
+        }
+        if let Some((sender, _)) = &self.channel {
+            for e in errors {
+                match sender.try_send((*e).clone()) {
+                    Ok(_) => {}
+                    Err(TrySendError::Full(e)) => ::ftlog::warn!("dead letter channel full, drop: {}", e),
+                    Err(TrySendError::Disconnected(_)) => break,
+                }
+            }
+        }
+    }
+}
+
+/// 读取隔离文件
+pub fn read_quarantine(path: &PathBuf) -> Result<Vec<QuarantineRecord>> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let mut records = vec![];
+    for record in reader.deserialize() {
+        records.push(record?);
+    }
+    Ok(records)
+}
+
+// 按字段顺序解析隔离记录的RAW(与读取时一致), 字段名仅用于定位出错字段; 仍然出错的行重新进入死信
+pub(crate) fn parse_quarantined<T: for<'de> Deserialize<'de>>(records: &[QuarantineRecord], headers: &csv::StringRecord, file_path: &Arc<PathBuf>) -> Vec<RowData<T>> {
+    records.iter().map(|record| {
+        let mut reader = csv::ReaderBuilder::new().has_headers(false).flexible(true).from_reader(record.raw.as_bytes());
+        let data = match reader.records().next() {
+            Some(Ok(fields)) => fields.deserialize::<T>(None).map_err(|e| ReadError::Deserialize {
+                path: file_path.clone(),
+                offset: record.offset,
+                line: record.line,
+                raw: Arc::from(record.raw.as_str()),
+                field: match e.kind() {
+                    csv::ErrorKind::Deserialize { err, .. } => err.field().and_then(|index| headers.get(index as usize)).map(Arc::from),
+                    _ => None,
+                },
+                source: Arc::new(e),
+            }),
+            Some(Err(e)) => Err(ReadError::BadRecord {
+                path: file_path.clone(),
+                offset: record.offset,
+                line: record.line,
+                source: Arc::new(e),
+            }),
+            None => Err(ReadError::Other(Arc::from(Box::<dyn std::error::Error + Send + Sync>::from(
+                format!("empty quarantine record at line {}", record.line),
+            )))),
+        };
+        RowData { offset: record.offset, line: record.line, size: record.raw.len(), data }
+    }).collect()
+}
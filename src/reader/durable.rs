@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+use dashmap::DashMap;
+use serde::Deserialize;
+use anyhow::{Result, bail};
+use super::checkpoint::ReaderCheckpoint;
+use super::msg_dispatcher::{MsgDispatcher, CertKeyT};
+use super::envelope::{ReadContext, RowData};
+
+/*
+DurableCursor: 具名订阅的消费位置
+位置含义与SubsReader.seek_pos一致; 每个发出的批次(按批次上限拆分后)记录其结束位置, 订阅方按顺序ack后才推进并持久化
+*/
+struct DurableCursor {
+    name: String, // 订阅名, 同一reader内唯一
+    checkpoint: ReaderCheckpoint, // 消费位置持久化
+    start_pos: Option<u64>, // 未激活时等待补发的起始位置; 激活后为None
+    pending: VecDeque<u64>, // 已发出未ack的批次结束位置(按发送顺序)
+    acked_pos: u64, // 已ack位置
+}
+
+/*
+DurableCursors: reader内所有具名订阅
+除subscribe/ack外, 均只在读线程中调用, 保证补发数据与实时数据有序且不重复
+*/
+#[derive(Default)]
+pub struct DurableCursors {
+    cursors: DashMap<CertKeyT, DurableCursor>,
+}
+
+impl DurableCursors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记具名订阅(未激活), 返回持久化的消费位置
+    pub fn register(&self, cert_key: CertKeyT, name: &str, checkpoint: ReaderCheckpoint) -> Result<u64> {
+        if self.cursors.iter().any(|c| c.name == name) {
+            bail!("durable subscription {} already exists", name);
+        }
+        let start_pos = checkpoint.load_seek_pos();
+        self.cursors.insert(cert_key, DurableCursor {
+            name: name.to_string(),
+            checkpoint,
+            start_pos: Some(start_pos),
+            pending: VecDeque::new(),
+            acked_pos: start_pos,
+        });
+        Ok(start_pos)
+    }
+
+    pub fn remove(&self, cert_key: CertKeyT) {
+        self.cursors.remove(&cert_key);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cursors.is_empty()
+    }
+
+    /// 确认最早一个未ack的批次, 返回新的已ack位置
+    pub fn ack(&self, cert_key: CertKeyT) -> Result<u64> {
+        let Some(mut cursor) = self.cursors.get_mut(&cert_key) else {
+            bail!("cert key {} is not a durable subscription", cert_key);
+        };
+        match cursor.pending.pop_front() {
+            Some(end_pos) => {
+                cursor.acked_pos = end_pos;
+                cursor.checkpoint.save(end_pos);
+            }
+            None => { // 文件重置后, 之前发出的批次不再需要ack
+                ::ftlog::debug!("{} no pending batch to ack", cursor.name);
+            }
+        }
+        Ok(cursor.acked_pos)
+    }
+
+    /// 已ack位置
+    pub fn acked_pos(&self, cert_key: CertKeyT) -> Option<u64> {
+        self.cursors.get(&cert_key).map(|c| c.acked_pos)
+    }
+
+    /// 未激活的订阅: 补发[start_pos, end_pos)后激活, 之后随实时数据一起分发
+    pub fn catch_up<T, R>(&self, end_pos: u64, dispatcher: &MsgDispatcher<T>, ctx: &ReadContext, read: R)
+    where
+        T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static,
+        R: Fn(u64, u64) -> Result<Vec<RowData<T>>>,
+    {
+        let waiting = self.cursors.iter()
+            .filter_map(|c| c.start_pos.filter(|start_pos| *start_pos <= end_pos).map(|start_pos| (*c.key(), start_pos)))
+            .collect::<Vec<_>>();
+        for (cert_key, start_pos) in waiting {
+            let mut has_data = false;
+            if start_pos < end_pos {
+                let rows = match read(start_pos, end_pos) {
+                    Ok(rows) => rows,
+                    Err(e) => { // 保持未激活, 下次读取时重试
+                        ::ftlog::error!("durable catch up read error: {:?}", e);
+                        continue;
+                    }
+                };
+                // 发送前记录批次, 不持有cursor锁发送, 避免与ack互锁
+                let send_result = dispatcher.send_to_with(&rows, ctx, cert_key, |chunk_ends| {
+                    has_data = true;
+                    if let Some(mut cursor) = self.cursors.get_mut(&cert_key) {
+                        cursor.pending.extend(chunk_end_pos(&rows, chunk_ends, end_pos));
+                    }
+                });
+                if let Err(e) = send_result {
+                    ::ftlog::error!("durable catch up send error: {:?}", e);
+                }
+            }
+            if let Some(mut cursor) = self.cursors.get_mut(&cert_key) {
+                cursor.start_pos = None;
+                if !has_data && cursor.acked_pos != end_pos {
+                    cursor.acked_pos = end_pos;
+                    cursor.checkpoint.save(end_pos);
+                }
+                ::ftlog::info!("durable {} active from {} to {}", cursor.name, start_pos, end_pos);
+            }
+            dispatcher.activate(cert_key);
+        }
+    }
+
+    /// 实时数据发送前调用(MsgDispatcher::dispatch_rows_with): 记录将要发出的各批次的结束位置; 未收到数据的订阅直接推进
+    /// 必须在发送前记录, 否则订阅方收到数据后立即ack时批次尚未登记
+    pub fn before_send<T, R>(&self, rows: &[RowData<T>], end_pos: u64, sent: &[(CertKeyT, Vec<usize>)], dispatcher: &MsgDispatcher<T>, ctx: &ReadContext, read: R)
+    where
+        T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static,
+        R: Fn(u64, u64) -> Result<Vec<RowData<T>>>,
+    {
+        for mut cursor in self.cursors.iter_mut() {
+            if cursor.start_pos.is_some() {
+                continue;
+            }
+            if let Some((_, chunk_ends)) = sent.iter().find(|(cert_key, _)| cert_key == cursor.key()) {
+                let positions = chunk_end_pos(rows, chunk_ends, end_pos).collect::<Vec<_>>();
+                cursor.pending.extend(positions);
+            } else if let Some(last) = cursor.pending.back_mut() { // 随最后一个未ack批次一起确认
+                *last = end_pos;
+            } else if cursor.acked_pos != end_pos {
+                cursor.acked_pos = end_pos;
+                cursor.checkpoint.save(end_pos);
+            }
+        }
+        self.catch_up(end_pos, dispatcher, ctx, read);
+    }
+
+    /// 文件被截断/替换, 读取位置重置为0
+    pub fn on_reset(&self) {
+        for mut cursor in self.cursors.iter_mut() {
+            cursor.pending.clear();
+            cursor.acked_pos = 0;
+            if cursor.start_pos.is_some() {
+                cursor.start_pos = Some(0);
+            }
+            cursor.checkpoint.save(0);
+        }
+    }
+}
+
+// 各批次的结束位置: 批次最后一行的下一行的偏移; 最后一个批次为本次读取的结束位置(之后的行与该订阅无关)
+fn chunk_end_pos<'a, T>(rows: &'a [RowData<T>], chunk_ends: &'a [usize], end_pos: u64) -> impl Iterator<Item = u64> + 'a {
+    chunk_ends.iter().enumerate().map(move |(i, end)| match rows.get(*end) {
+        Some(row) if i + 1 < chunk_ends.len() => row.offset,
+        _ => end_pos,
+    })
+}
@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use crate::common::model::FileIdentity;
+use crate::common::timer::get_coarse_timestamp_ms;
+use super::error::ReadResult;
+
+/*
+Envelope: 带来源信息的数据行(subscribe_envelope订阅时使用)
+offset含义与SubsReader.seek_pos一致: CSV为该行起始字节偏移, DBF为记录序号
+*/
+#[derive(Debug, Clone)]
+pub struct Envelope<T> {
+    pub file_path: Arc<PathBuf>, // 来源文件
+    pub identity: FileIdentity, // 读取时的文件身份
+    pub offset: u64, // 字节偏移(CSV)/记录序号(DBF)
+    pub line: u64, // 行号, 从1开始(CSV含表头行, DBF为记录序号+1)
+    pub notify_time: u64, // 触发本次读取的通知时间(ms), 非通知触发时为读取时间
+    pub seq: u64, // reader内按文件顺序递增的序号, 由行号推导, 重放/快照/补发时不变; 文件重置后接着递增
+    pub data: T,
+}
+
+/*
+RowData: 读取的一行数据及其在文件中的位置
+*/
+#[derive(Debug, Clone)]
+pub struct RowData<T> {
+    pub offset: u64, // 字节偏移(CSV)/记录序号(DBF)
+    pub line: u64, // 行号
+    pub size: usize, // 原始记录长度(字节), 用于按字节数拆分批次
+    pub data: ReadResult<T>,
+}
+
+impl<T> RowData<T> {
+    /// 无位置信息的数据(外部直接分发时)
+    pub fn detached(data: ReadResult<T>) -> Self {
+        Self { offset: 0, line: 0, size: 0, data }
+    }
+}
+
+/*
+ReadContext: 单次读取的来源信息, 分发时填入Envelope
+*/
+#[derive(Debug, Clone, Default)]
+pub struct ReadContext {
+    pub file_path: Arc<PathBuf>,
+    pub identity: FileIdentity,
+    pub notify_time: u64,
+    pub read_from: Option<u64>, // 本批次的读取起点(CSV字节偏移/DBF记录序号), 暂停的订阅需要重读时使用; 未知时为None
+}
+
+impl ReadContext {
+    /// 读取时获取文件身份; 获取失败(如文件已删除)时为默认值
+    pub fn new(file_path: &Arc<PathBuf>, notify_time: u64) -> Self {
+        Self {
+            file_path: file_path.clone(),
+            identity: FileIdentity::of(file_path).unwrap_or_default(),
+            notify_time,
+            read_from: None,
+        }
+    }
+
+    /// 设置读取起点
+    pub fn with_read_from(mut self, read_from: u64) -> Self {
+        self.read_from = Some(read_from);
+        self
+    }
+
+    /// 非通知触发的读取(首次读取/补发/回放), 以当前时间作为通知时间
+    pub fn now(file_path: &Arc<PathBuf>) -> Self {
+        Self::new(file_path, get_coarse_timestamp_ms())
+    }
+}
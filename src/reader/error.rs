@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+type ErrorSource = Arc<dyn std::error::Error + Send + Sync>;
+
+/// 数据通道中的结果类型
+pub type ReadResult<T> = std::result::Result<T, ReadError>;
+
+/*
+ReadError: 数据通道中的读取错误, 字段均为Arc, 分发给多个订阅者时clone开销小
+offset/line含义与Envelope一致: CSV为字节偏移/行号(含表头), DBF为记录序号/序号+1
+*/
+#[derive(Debug, Clone)]
+pub enum ReadError {
+    // 读取文件失败
+    Io {
+        path: Arc<PathBuf>,
+        offset: u64,
+        line: u64,
+        source: Arc<std::io::Error>,
+    },
+    // 字段按文件编码(GBK/UTF8)解码失败
+    Decode {
+        path: Arc<PathBuf>,
+        offset: u64,
+        line: u64,
+        raw: Arc<str>, // 原始记录(有损解码)
+        field: Option<Arc<str>>, // 字段名
+    },
+    // CSV格式错误的记录(如引号未闭合)
+    BadRecord {
+        path: Arc<PathBuf>,
+        offset: u64,
+        line: u64,
+        source: ErrorSource,
+    },
+    // 记录反序列化为T失败
+    Deserialize {
+        path: Arc<PathBuf>,
+        offset: u64,
+        line: u64,
+        raw: Arc<str>,
+        field: Option<Arc<str>>,
+        source: ErrorSource,
+    },
+    // 非读取产生的错误(外部通过dispatch_data分发)
+    Other(ErrorSource),
+}
+
+impl ReadError {
+    /// 来源文件, Other为None
+    pub fn path(&self) -> Option<&Arc<PathBuf>> {
+        match self {
+            ReadError::Io { path, .. } | ReadError::Decode { path, .. }
+            | ReadError::BadRecord { path, .. } | ReadError::Deserialize { path, .. } => Some(path),
+            ReadError::Other(_) => None,
+        }
+    }
+
+    /// 字节偏移(CSV)/记录序号(DBF)
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            ReadError::Io { offset, .. } | ReadError::Decode { offset, .. }
+            | ReadError::BadRecord { offset, .. } | ReadError::Deserialize { offset, .. } => Some(*offset),
+            ReadError::Other(_) => None,
+        }
+    }
+
+    /// 行号
+    pub fn line(&self) -> Option<u64> {
+        match self {
+            ReadError::Io { line, .. } | ReadError::Decode { line, .. }
+            | ReadError::BadRecord { line, .. } | ReadError::Deserialize { line, .. } => Some(*line),
+            ReadError::Other(_) => None,
+        }
+    }
+
+    /// 出错的字段名
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            ReadError::Decode { field, .. } | ReadError::Deserialize { field, .. } => field.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// 原始记录文本
+    pub fn raw(&self) -> Option<&str> {
+        match self {
+            ReadError::Decode { raw, .. } | ReadError::Deserialize { raw, .. } => Some(raw),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::Io { path, offset, line, source } => {
+                write!(f, "{}:{} (offset {}) io error: {}", path.display(), line, offset, source)
+            }
+            ReadError::Decode { path, offset, line, raw, field } => {
+                write!(f, "{}:{} (offset {}) decode error at field {:?}; raw: {}", path.display(), line, offset, field, raw)
+            }
+            ReadError::BadRecord { path, offset, line, source } => {
+                write!(f, "{}:{} (offset {}) bad record: {}", path.display(), line, offset, source)
+            }
+            ReadError::Deserialize { path, offset, line, raw, field, source } => {
+                write!(f, "{}:{} (offset {}) deserialize error at field {:?}: {}; raw: {}", path.display(), line, offset, field, source, raw)
+            }
+            ReadError::Other(source) => write!(f, "{}", source),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io { source, .. } => Some(source.as_ref()),
+            ReadError::BadRecord { source, .. } | ReadError::Deserialize { source, .. } => Some(source.as_ref()),
+            ReadError::Decode { .. } | ReadError::Other(_) => None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for ReadError {
+    fn from(e: anyhow::Error) -> Self {
+        let source: Box<dyn std::error::Error + Send + Sync> = e.into();
+        ReadError::Other(Arc::from(source))
+    }
+}
@@ -9,6 +9,7 @@ use std::any::{Any, TypeId};
 use std::path::{PathBuf};
 use std::sync::{Arc, Mutex};
 use super::{subscribe_reader::*};
+use super::options::ReaderOptions;
 use crate::reader::msg_dispatcher::CertKeyT;
 use crate::common::model::*;
 
@@ -65,6 +66,14 @@ fn get_dbf_map() -> &'static DashMap<TypeId, FxHashMap<PathBuf, (Option<Arc<dyn
 
 /// 创建或者返回已有的reader
 pub fn get_or_create_csv_reader<T>(path: &PathBuf, increment: bool, enc_type: EncType) -> Result<Arc<CsvReader<T>>>
+where
+    T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static
+{
+    get_or_create_csv_reader_with_options(path, increment, enc_type, ReaderOptions::default())
+}
+
+/// 创建或者返回已有的reader; options仅在首次创建时生效
+pub fn get_or_create_csv_reader_with_options<T>(path: &PathBuf, increment: bool, enc_type: EncType, options: ReaderOptions) -> Result<Arc<CsvReader<T>>>
 where
     T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static
 {
@@ -96,7 +105,7 @@ where
     }
 
     // 创建新 reader 并存入全局 map
-    let csv_reader = CsvReader::new(path.clone(), increment, enc_type)?;
+    let csv_reader = CsvReader::with_options(path.clone(), increment, enc_type, options)?;
     let reader_arc: Arc<CsvReader<T>> = Arc::new(csv_reader);
 
     // 转换为 trait object 存储
@@ -164,6 +173,14 @@ where
 
 /// 创建或者返回已有的reader
 pub fn get_or_create_dbf_reader<T>(path: &PathBuf, increment: bool, enc_type: EncType) -> Result<Arc<DbfReader<T>>>
+where
+    T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static
+{
+    get_or_create_dbf_reader_with_options(path, increment, enc_type, ReaderOptions::default())
+}
+
+/// 创建或者返回已有的reader; options仅在首次创建时生效
+pub fn get_or_create_dbf_reader_with_options<T>(path: &PathBuf, increment: bool, enc_type: EncType, options: ReaderOptions) -> Result<Arc<DbfReader<T>>>
 where
     T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static
 {
@@ -195,7 +212,7 @@ where
     }
 
     // 创建新 reader 并存入全局 map
-    let dbf_reader = DbfReader::new(path.clone(), increment, enc_type)?;
+    let dbf_reader = DbfReader::with_options(path.clone(), increment, enc_type, options)?;
     let reader_arc: Arc<DbfReader<T>> = Arc::new(dbf_reader);
 
     // 转换为 trait object 存储
@@ -4,6 +4,21 @@ pub mod manager;
 pub mod msg_dispatcher;
 pub mod csv_reader;
 pub mod dbf_reader;
+pub mod checkpoint;
+pub mod options;
+pub mod durable;
+pub mod envelope;
+pub mod error;
+pub mod dead_letter;
+pub mod stream;
+pub mod retention;
+pub mod backpressure;
+pub mod subscription;
+pub mod consumer_group;
+pub mod pause;
+pub mod batching;
+#[cfg(feature = "async")]
+pub mod async_stream;
 
 /*
 
@@ -1,18 +1,150 @@
-use std::sync::{atomic::{AtomicI32, AtomicBool, Ordering}, Arc};
-use crossbeam::channel::{Sender};
+use std::sync::{atomic::{AtomicI32, AtomicBool, AtomicU64, Ordering}, Arc};
+use crossbeam::channel::Sender;
 use serde::Deserialize;
 use dashmap::DashMap;
-use rustc_hash::FxHashMap;
-use anyhow::Result;
+use rustc_hash::{FxHashMap, FxHashSet};
+use once_cell::sync::OnceCell;
+use anyhow::{Result, bail};
+use super::envelope::{Envelope, ReadContext, RowData};
+use super::error::{ReadError, ReadResult};
+use super::dead_letter::DeadLetter;
+use super::stream::StreamItem;
+use super::backpressure::{Delivery, OverflowSender, OverflowStats};
+use super::consumer_group::{ConsumerGroup, GroupOptions};
+use super::pause::{PauseMode, PauseState, Resumed};
+use super::batching::BatchLimits;
+
+/*
+CertSender: 订阅者的发送通道, 按订阅方式发送原始数据、带来源信息的数据或带控制事件的数据流
+通道满时按订阅时选择的溢出策略处理
+*/
+#[derive(Clone)]
+pub enum CertSender<T> {
+    Data(OverflowSender<Vec<ReadResult<T>>>),
+    Envelope(OverflowSender<Vec<ReadResult<Envelope<T>>>>),
+    Stream(OverflowSender<StreamItem<T>>),
+    #[cfg(feature = "async")]
+    Async(OverflowSender<StreamItem<T>>), // 与Stream相同, 接收端为异步Stream
+}
+
+impl<T: Clone + Send + Sync + 'static> CertSender<T> {
+    // 按行号列表组装并发送, rows中的seq与rows一一对应
+    fn send_rows(&self, rows: &[RowData<T>], seqs: &[u64], indexes: &[usize], ctx: &ReadContext) -> Delivery {
+        match self {
+            CertSender::Data(sender) => {
+                let msgs = indexes.iter().map(|i| rows[*i].data.clone()).collect();
+                sender.send(msgs)
+            }
+            CertSender::Envelope(sender) => {
+                let msgs = indexes.iter().map(|i| {
+                    let row = &rows[*i];
+                    row.data.clone().map(|data| Envelope {
+                        file_path: ctx.file_path.clone(),
+                        identity: ctx.identity,
+                        offset: row.offset,
+                        line: row.line,
+                        notify_time: ctx.notify_time,
+                        seq: seqs[*i],
+                        data,
+                    })
+                }).collect();
+                sender.send(msgs)
+            }
+            CertSender::Stream(sender) => {
+                let msgs = indexes.iter().map(|i| rows[*i].data.clone()).collect();
+                sender.send(StreamItem::Data(msgs))
+            }
+            #[cfg(feature = "async")]
+            CertSender::Async(sender) => {
+                let msgs = indexes.iter().map(|i| rows[*i].data.clone()).collect();
+                sender.send(StreamItem::Data(msgs))
+            }
+        }
+    }
+
+    // 是否接收控制事件
+    fn receives_events(&self) -> bool {
+        match self {
+            CertSender::Stream(_) => true,
+            #[cfg(feature = "async")]
+            CertSender::Async(_) => true,
+            _ => false,
+        }
+    }
+
+    // 发送控制事件, 非数据流订阅者忽略
+    fn send_event(&self, event: StreamItem<T>) -> Delivery {
+        match self {
+            CertSender::Stream(sender) => sender.send(event),
+            #[cfg(feature = "async")]
+            CertSender::Async(sender) => sender.send(event),
+            _ => Delivery::Sent,
+        }
+    }
+
+    // Coalesce合并不超过批次上限
+    fn with_batch_limits(self, batch: &BatchLimits) -> Self {
+        match self {
+            CertSender::Data(sender) => CertSender::Data(sender.with_batch_limits(batch)),
+            CertSender::Envelope(sender) => CertSender::Envelope(sender.with_batch_limits(batch)),
+            CertSender::Stream(sender) => CertSender::Stream(sender.with_batch_limits(batch)),
+            #[cfg(feature = "async")]
+            CertSender::Async(sender) => CertSender::Async(sender.with_batch_limits(batch)),
+        }
+    }
+
+    fn stats(&self) -> OverflowStats {
+        match self {
+            CertSender::Data(sender) => sender.stats(),
+            CertSender::Envelope(sender) => sender.stats(),
+            CertSender::Stream(sender) => sender.stats(),
+            #[cfg(feature = "async")]
+            CertSender::Async(sender) => sender.stats(),
+        }
+    }
+}
+
+/// 订阅过滤条件, 可捕获状态(如账户集合、正则、共享配置)
+pub type Filter<T> = Box<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// 由匹配函数和校验数据构造过滤条件, 对应原有的 fn(verify_data, data) 订阅方式
+pub fn verify_filter<T: 'static>(verify_data: &str, dispatcher_func: fn(&str, &T) -> bool) -> Filter<T> {
+    let verify_data = verify_data.to_string();
+    Box::new(move |data| dispatcher_func(&verify_data, data))
+}
+
+/// 从数据中提取路由key(如资金账号), 每行只调用一次
+pub type KeyFn<T> = fn(&T) -> &str;
+
+// 去重后的key, 保持原顺序
+fn unique_keys(keys: &[&str]) -> Vec<String> {
+    let mut seen = FxHashSet::default();
+    keys.iter().filter(|key| seen.insert(**key)).map(|key| key.to_string()).collect()
+}
+
+// 按key订阅的过滤条件, 单独发送(如补发)时使用
+fn key_filter<T: 'static>(keys: &[&str], key_fn: KeyFn<T>) -> Filter<T> {
+    let key_set = keys.iter().map(|key| key.to_string()).collect::<FxHashSet<_>>();
+    Box::new(move |data| key_set.contains(key_fn(data)))
+}
+
 /*
 DispatcherCert: 数据分流凭证
 */
 struct DispatcherCert<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> {
     pub cert_key: i32, // 唯一凭证
-    pub dispatcher_func: fn(&str, &T) -> bool, // 匹配函数(verify_data, data)
-    pub verify_data: String,
-    pub is_running: Arc<AtomicBool>, // 删除凭据
-    pub send_channel: Sender<Vec<Result<T>>>, // 发送通道
+    pub filter: Filter<T>, // 过滤条件
+    pub keys: Vec<String>, // 按key订阅时的key列表, 为空时按filter逐行判断
+    pub group: Option<Arc<ConsumerGroup<T>>>, // 消费组成员, 由消费组分配数据
+    pub is_running: Arc<AtomicBool>, // 是否接收分发(删除或未激活时为false)
+    pub send_channel: CertSender<T>, // 发送通道
+}
+
+impl<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> DispatcherCert<T> {
+    // 错误数据发给所有订阅者
+    fn accept(&self, data: &ReadResult<T>) -> bool {
+        data.as_ref().map_or(true, |data| (self.filter)(data))
+    }
 }
 
 /*
@@ -23,6 +155,20 @@ pub type CertKeyT = i32;
 pub struct MsgDispatcher<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> {
     dispatcher_certs: DashMap<CertKeyT, DispatcherCert<T>>,
     pub subscriber_count: Arc<AtomicI32>, // 订阅者数量只增不减
+    seq_base: AtomicU64, // 文件重置前的最大序号, 重置后的序号在此基础上继续
+    max_seq: AtomicU64, // 已分发的最大序号
+    dead_letter: Option<Arc<DeadLetter>>, // 错误行去处, 为None时错误行发给所有订阅者
+    key_fn: OnceCell<KeyFn<T>>, // 路由key提取函数
+    key_index: DashMap<String, Vec<CertKeyT>>, // key -> 按key订阅的cert
+    groups: DashMap<String, Arc<ConsumerGroup<T>>>, // 竞争消费组
+    paused: DashMap<CertKeyT, PauseState<T>>, // 暂停中的订阅
+    batch: BatchLimits, // 单次发送的批次上限
+}
+
+impl <T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> Default for MsgDispatcher<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl <T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> MsgDispatcher<T> {
@@ -30,110 +176,515 @@ impl <T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> MsgDispatcher<
         MsgDispatcher {
             subscriber_count: Arc::new(AtomicI32::new(1)),
             dispatcher_certs: DashMap::new(),
+            seq_base: AtomicU64::new(0),
+            max_seq: AtomicU64::new(0),
+            dead_letter: None,
+            key_fn: OnceCell::new(),
+            key_index: DashMap::new(),
+            groups: DashMap::new(),
+            paused: DashMap::new(),
+            batch: BatchLimits::default(),
         }
     }
 
+    /// 错误行发到死信, 不再发给订阅者
+    pub fn with_dead_letter(dead_letter: Arc<DeadLetter>) -> Self {
+        MsgDispatcher { dead_letter: Some(dead_letter), ..Self::new() }
+    }
+
+    /// 设置单次发送的批次上限, 超出时拆分发送
+    pub fn with_batch_limits(mut self, batch: BatchLimits) -> Self {
+        self.batch = batch;
+        self
+    }
+
+    /// 单次发送的批次上限
+    pub fn batch_limits(&self) -> &BatchLimits {
+        &self.batch
+    }
+
+    /// 错误行去处
+    pub fn dead_letter(&self) -> Option<&Arc<DeadLetter>> {
+        self.dead_letter.as_ref()
+    }
+
+    // 是否发给订阅者: 配置死信后错误行不发给订阅者
+    fn deliverable(&self, row: &RowData<T>) -> bool {
+        self.dead_letter.is_none() || row.data.is_ok()
+    }
+
     /// 获取凭证;凭证仅用于移除channel
     pub fn get_cert(&self) -> CertKeyT {
         self.subscriber_count.fetch_add(1, Ordering::Relaxed) as CertKeyT
     }
 
     /// 注册channel
-    pub fn subscribe(&self, verify_data: &str, dispatcher_func: fn(&str, &T) -> bool, sender: Sender<Vec<Result<T>>>, cert_key: CertKeyT) {
+    pub fn subscribe(&self, verify_data: &str, dispatcher_func: fn(&str, &T) -> bool, sender: Sender<Vec<ReadResult<T>>>, cert_key: CertKeyT) {
+        self.subscribe_filter(verify_filter(verify_data, dispatcher_func), sender, cert_key);
+    }
+
+    /// 按过滤条件注册channel
+    pub fn subscribe_filter(&self, filter: Filter<T>, sender: impl Into<OverflowSender<Vec<ReadResult<T>>>>, cert_key: CertKeyT) {
+        self.insert_cert(filter, vec![], CertSender::Data(sender.into()), cert_key, true);
+    }
+
+    /// 设置路由key提取函数, 只能设置一次
+    pub fn set_key_fn(&self, key_fn: KeyFn<T>) -> Result<()> {
+        if self.key_fn.set(key_fn).is_err() {
+            bail!("key fn already set");
+        }
+        Ok(())
+    }
+
+    /// 按key订阅: 每行提取一次key后查表分发, 不再逐个订阅者判断; 需要先设置key提取函数
+    pub fn subscribe_keys(&self, keys: &[&str], sender: impl Into<OverflowSender<Vec<ReadResult<T>>>>, cert_key: CertKeyT) -> Result<()> {
+        let Some(key_fn) = self.key_fn.get().copied() else {
+            bail!("key fn not set");
+        };
+        let unique = unique_keys(keys);
+        self.index_keys(cert_key, &unique); // 先建索引, 分发时只查找已注册的cert
+        self.insert_cert(key_filter(keys, key_fn), unique, CertSender::Data(sender.into()), cert_key, true);
+        Ok(())
+    }
+
+    /// 更新订阅的过滤条件, 从下一个分发批次开始生效; 按key订阅的改为按过滤条件逐行判断
+    pub fn update_filter(&self, cert_key: CertKeyT, filter: Filter<T>) -> Result<()> {
+        let Some(mut cert) = self.dispatcher_certs.get_mut(&cert_key) else {
+            bail!("cert key {} not subscribed", cert_key);
+        };
+        self.unindex_keys(cert_key, &cert.keys);
+        cert.keys = vec![];
+        cert.filter = filter;
+        ::ftlog::info!("cert={} filter updated", cert_key);
+        Ok(())
+    }
+
+    /// 更新订阅的key列表(替换原有的), 从下一个分发批次开始生效; 需要先设置key提取函数
+    pub fn update_keys(&self, cert_key: CertKeyT, keys: &[&str]) -> Result<()> {
+        let Some(key_fn) = self.key_fn.get().copied() else {
+            bail!("key fn not set");
+        };
+        let Some(mut cert) = self.dispatcher_certs.get_mut(&cert_key) else {
+            bail!("cert key {} not subscribed", cert_key);
+        };
+        // 持有cert写锁修改索引, 分发中的批次仍按原key分组
+        self.unindex_keys(cert_key, &cert.keys);
+        let unique = unique_keys(keys);
+        self.index_keys(cert_key, &unique);
+        cert.keys = unique;
+        cert.filter = key_filter(keys, key_fn);
+        ::ftlog::info!("cert={} keys updated: {:?}", cert_key, keys);
+        Ok(())
+    }
+
+    fn index_keys(&self, cert_key: CertKeyT, keys: &[String]) {
+        for key in keys {
+            let mut certs = self.key_index.entry(key.clone()).or_default();
+            if !certs.contains(&cert_key) { // 同一cert只登记一次, 避免重复投递
+                certs.push(cert_key);
+            }
+        }
+    }
+
+    fn unindex_keys(&self, cert_key: CertKeyT, keys: &[String]) {
+        for key in keys {
+            self.key_index.remove_if_mut(key, |_, certs| {
+                certs.retain(|key| *key != cert_key);
+                certs.is_empty()
+            });
+        }
+    }
+
+    /// 注册channel但暂不接收分发, 由读线程补发数据后调用activate
+    pub fn subscribe_inactive(&self, filter: Filter<T>, sender: impl Into<OverflowSender<Vec<ReadResult<T>>>>, cert_key: CertKeyT) {
+        self.insert_cert(filter, vec![], CertSender::Data(sender.into()), cert_key, false);
+    }
+
+    /// 注册带来源信息的channel
+    pub fn subscribe_envelope(&self, filter: Filter<T>, sender: impl Into<OverflowSender<Vec<ReadResult<Envelope<T>>>>>, cert_key: CertKeyT) {
+        self.insert_cert(filter, vec![], CertSender::Envelope(sender.into()), cert_key, true);
+    }
+
+    /// 注册带控制事件的数据流channel
+    pub fn subscribe_stream(&self, filter: Filter<T>, sender: impl Into<OverflowSender<StreamItem<T>>>, cert_key: CertKeyT) {
+        self.insert_cert(filter, vec![], CertSender::Stream(sender.into()), cert_key, true);
+    }
+
+    /// 注册带控制事件的异步数据流
+    #[cfg(feature = "async")]
+    pub fn subscribe_async(&self, filter: Filter<T>, sender: impl Into<OverflowSender<StreamItem<T>>>, cert_key: CertKeyT) {
+        self.insert_cert(filter, vec![], CertSender::Async(sender.into()), cert_key, true);
+    }
+
+    fn insert_cert(&self, filter: Filter<T>, keys: Vec<String>, sender: CertSender<T>, cert_key: CertKeyT, is_running: bool) {
+        self.dispatcher_certs.insert(cert_key, DispatcherCert {
+            cert_key,
+            filter,
+            keys,
+            group: None,
+            send_channel: sender.with_batch_limits(&self.batch),
+            is_running: Arc::new(AtomicBool::new(is_running)),
+        });
+    }
+
+    /// 创建竞争消费组: 满足过滤条件的每行只发给组内一个成员, 按partition提取的key分配, 同一key的数据由同一成员按顺序处理
+    pub fn create_group(&self, name: &str, filter: Filter<T>, partition: KeyFn<T>) -> Result<()> {
+        self.create_group_with(name, filter, partition, GroupOptions::default())
+    }
+
+    /// 创建竞争消费组, 指定暂存行数上限及超出时的处理方式
+    pub fn create_group_with(&self, name: &str, filter: Filter<T>, partition: KeyFn<T>, options: GroupOptions) -> Result<()> {
+        match self.groups.entry(name.to_string()) {
+            dashmap::mapref::entry::Entry::Occupied(_) => bail!("group {} already exists", name),
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                entry.insert(Arc::new(ConsumerGroup::new(name, filter, partition, self.batch, options)));
+            }
+        }
+        Ok(())
+    }
+
+    /// 删除消费组, 组内仍有成员时返回Err; 暂存的行一并丢弃
+    pub fn remove_group(&self, name: &str) -> Result<()> {
+        match self.groups.remove_if(name, |_, group| group.members() == 0) {
+            Some((_, group)) => {
+                ::ftlog::info!("remove group {}, discard {} pending rows", name, group.pending());
+                Ok(())
+            }
+            None if self.groups.contains_key(name) => bail!("group {} has members", name),
+            None => bail!("group {} not exists", name),
+        }
+    }
+
+    /// 加入消费组; sender持有接收端(OverflowSender::with_drain)时, 成员退出后取回未取走的批次重新分配
+    pub fn join_group(&self, name: &str, sender: impl Into<OverflowSender<Vec<ReadResult<T>>>>, cert_key: CertKeyT) -> Result<()> {
+        let Some(group) = self.group(name) else {
+            bail!("group {} not exists", name);
+        };
+        let sender = sender.into().with_batch_limits(&self.batch);
         self.dispatcher_certs.insert(cert_key, DispatcherCert {
             cert_key,
-            verify_data: verify_data.to_string(),
-            dispatcher_func,
-            send_channel: sender,
+            filter: Box::new(|_| false), // 不单独发送(补发/回放), 由消费组分配
+            keys: vec![],
+            group: Some(group.clone()),
+            send_channel: CertSender::Data(sender.clone()),
             is_running: Arc::new(AtomicBool::new(true)),
         });
+        for closed in group.join(cert_key, sender) {
+            self.handle_delivery(closed, Delivery::Closed);
+        }
+        Ok(())
+    }
+
+    /// 获取消费组
+    pub fn group(&self, name: &str) -> Option<Arc<ConsumerGroup<T>>> {
+        self.groups.get(name).map(|group| group.clone())
+    }
+
+    /// 激活通过subscribe_inactive注册的channel
+    pub fn activate(&self, cert_key: CertKeyT) {
+        if let Some(cert) = self.dispatcher_certs.get(&cert_key) {
+            cert.is_running.store(true, Ordering::Relaxed);
+        }
     }
 
     ///  获取凭证并注册, 返回凭证id,后续拿凭证id移除channel
-    pub fn get_cert_and_subscribe(&self, verify_data: &str, dispatcher_func: fn(&str, &T) -> bool, sender: Sender<Vec<Result<T>>>) -> CertKeyT {
+    pub fn get_cert_and_subscribe(&self, verify_data: &str, dispatcher_func: fn(&str, &T) -> bool, sender: Sender<Vec<ReadResult<T>>>) -> CertKeyT {
         let cert_key = self.get_cert();
         self.subscribe(verify_data, dispatcher_func, sender, cert_key);
         cert_key
     }
 
+    // 每行的序号由行号推导, 同一行重放/快照/补发时序号不变; 无位置信息的行为0
+    fn row_seqs(&self, rows: &[RowData<T>]) -> Vec<u64> {
+        let base = self.seq_base.load(Ordering::Acquire);
+        let seqs = rows.iter().map(|row| if row.line == 0 { 0 } else { base + row.line }).collect::<Vec<_>>();
+        if let Some(max) = seqs.iter().max() {
+            self.max_seq.fetch_max(*max, Ordering::AcqRel);
+        }
+        seqs
+    }
+
+    /// 文件被截断/替换, 之后的序号接在已分发的最大序号之后
+    pub fn on_reset(&self) {
+        self.seq_base.store(self.max_seq.load(Ordering::Acquire), Ordering::Release);
+    }
+
     /// 分发数据
-    pub fn dispatch(&self, msgs: Vec<Result<T>>) -> anyhow::Result<()> {
-        ::ftlog::info!("ready dispatch:len={}",msgs.len());
-        // 使用局部的 FxHashMap 作为缓冲区
-        let mut dispatcher_buff: FxHashMap<CertKeyT, Vec<Result<T>>> = FxHashMap::default();
-
-        for msg in msgs.into_iter() {
-            for entry in self.dispatcher_certs.iter() {
-                let cert = entry.value();
-                if cert.is_running.load(Ordering::Relaxed) { //只发给运行中的chan
-                    match &msg {
-                        Ok(data) => {
-                            if (cert.dispatcher_func)(&cert.verify_data, &data) {  // 传递 &T 而不是 &Result<T>
-                                // 将数据添加到对应cert的缓冲区中
-                                ::ftlog::trace!("data insert to cert:{}",cert.cert_key);
-                                dispatcher_buff.entry(cert.cert_key)
-                                    .or_insert_with(Vec::new)
-                                    .push(Ok(data.clone()));
-                            }
-                        },
-                        Err(e) => {
-                            ::ftlog::trace!("err data insert to cert:{}",cert.cert_key);
-                            // 给所有chan发送该错误消息的克隆版本
-                            dispatcher_buff.entry(cert.cert_key)
-                                .or_insert_with(Vec::new)
-                                .push(Err(anyhow::format_err!("{}", e))); // 使用format_err克隆错误
-                        }
+    pub fn dispatch(&self, msgs: Vec<ReadResult<T>>) -> anyhow::Result<()> {
+        let rows = msgs.into_iter().map(RowData::detached).collect::<Vec<_>>();
+        self.dispatch_rows(&rows, &ReadContext::default()).map(|_| ())
+    }
+
+    /// 分发读取的数据行, 返回收到数据的cert
+    pub fn dispatch_rows(&self, rows: &[RowData<T>], ctx: &ReadContext) -> anyhow::Result<Vec<CertKeyT>> {
+        self.dispatch_rows_with(rows, ctx, |_| {})
+    }
+
+    /// 分发读取的数据行; 确定接收的cert后, 发送前调用before_send(订阅方可能在收到数据后立即ack),
+    /// 参数为各cert及其按批次上限拆分后每个批次的结束下标(批次最后一行的下一行在rows中的下标)
+    pub fn dispatch_rows_with<F: FnOnce(&[(CertKeyT, Vec<usize>)])>(&self, rows: &[RowData<T>], ctx: &ReadContext, before_send: F) -> anyhow::Result<Vec<CertKeyT>> {
+        ::ftlog::info!("ready dispatch:len={}", rows.len());
+        let seqs = self.row_seqs(rows);
+        // 使用局部的 FxHashMap 作为缓冲区, 记录每个cert需要的行
+        let mut dispatcher_buff: FxHashMap<CertKeyT, Vec<usize>> = FxHashMap::default();
+        if let Some(dead_letter) = &self.dead_letter {
+            dead_letter.push(&rows.iter().filter_map(|row| row.data.as_ref().err()).collect::<Vec<&ReadError>>());
+        }
+
+        // 本批次运行中的订阅者: 按filter判断的逐行检查, 按key订阅的查表
+        // 分组完成前持有cert读锁, 更新过滤条件/key会等到本批次分组完成, 从下一批次生效
+        let mut filtered = vec![];
+        let mut keyed = vec![];
+        let mut running_keyed = FxHashSet::default();
+        for cert in self.dispatcher_certs.iter().filter(|cert| cert.is_running.load(Ordering::Relaxed)) { //只发给运行中的chan
+            if cert.group.is_some() { // 消费组成员由消费组分配
+                continue;
+            } else if cert.keys.is_empty() {
+                filtered.push(cert);
+            } else {
+                running_keyed.insert(cert.cert_key);
+                keyed.push(cert);
+            }
+        }
+        let key_fn = self.key_fn.get();
+        for (index, row) in rows.iter().enumerate() {
+            if !self.deliverable(row) {
+                continue;
+            }
+            for cert in filtered.iter().filter(|cert| cert.accept(&row.data)) {
+                ::ftlog::trace!("data insert to cert:{}",cert.cert_key);
+                dispatcher_buff.entry(cert.cert_key).or_default().push(index);
+            }
+            match (&row.data, key_fn) {
+                (Ok(data), Some(key_fn)) => if let Some(certs) = self.key_index.get(key_fn(data)) {
+                    for cert_key in certs.iter().filter(|cert_key| running_keyed.contains(*cert_key)) {
+                        dispatcher_buff.entry(*cert_key).or_default().push(index);
                     }
                 }
+                (Err(_), _) => for cert_key in &running_keyed { // 错误数据发给所有订阅者
+                    dispatcher_buff.entry(*cert_key).or_default().push(index);
+                }
+                _ => {}
             }
         }
-        
+        drop(filtered);
+        drop(keyed);
 
-        // 批量发送并清空buffer; 减少一次mem copy
-        for (key, buffer) in dispatcher_buff.drain() { // send
-            let data_len = buffer.len();
-            if let Some(cert) = self.dispatcher_certs.get(&key) {
-                if cert.is_running.load(Ordering::Relaxed) {
-                    if let Err(e) = cert.send_channel.send(buffer) {
-                        ::ftlog::error!("send error:{:?}", e);
-                    } else {
-                        ::ftlog::debug!("send to cert={} success, len={}",key, data_len);
-                    }
-                }
+        before_send(&dispatcher_buff.iter().map(|(key, indexes)| (*key, self.chunk_ends(rows, indexes))).collect::<Vec<_>>());
+
+        // 批量发送并清空buffer
+        let mut sent_certs = Vec::with_capacity(dispatcher_buff.len());
+        for (key, indexes) in dispatcher_buff.drain() { // send
+            let send_channel = match self.dispatcher_certs.get(&key) {
+                Some(cert) if cert.is_running.load(Ordering::Relaxed) => cert.send_channel.clone(),
+                _ => continue,
+            };
+            if let Some(mut paused) = self.paused.get_mut(&key) { // 暂停中: 缓存或记录重读位置
+                paused.push(rows, &indexes, ctx.read_from);
+                continue;
+            }
+            let delivery = self.send_chunks(&send_channel, rows, &seqs, &indexes, ctx); // 不持有map锁发送
+            if self.handle_delivery(key, delivery) {
+                ::ftlog::debug!("send to cert={} success, len={}", key, indexes.len());
+                sent_certs.push(key);
+            }
+        }
+
+
+        // 消费组: 每行只发给组内一个成员
+        let groups = self.groups.iter().map(|group| group.clone()).collect::<Vec<_>>();
+        for group in groups {
+            for closed in group.dispatch(rows, |row| self.deliverable(row)) {
+                self.handle_delivery(closed, Delivery::Closed);
             }
         }
 
+        Ok(sent_certs)
+    }
+
+    // 处理发送结果, 返回是否已放入通道; 按溢出策略断开或接收端已关闭的订阅在此移除
+    fn handle_delivery(&self, cert_key: CertKeyT, delivery: Delivery) -> bool {
+        match delivery {
+            Delivery::Sent => true,
+            Delivery::Dropped => {
+                ::ftlog::warn!("cert={} channel full, batch dropped", cert_key);
+                false
+            }
+            Delivery::Disconnected => {
+                ::ftlog::warn!("cert={} channel full, disconnected", cert_key);
+                self.unsubscribe(cert_key);
+                false
+            }
+            Delivery::Closed => {
+                ::ftlog::info!("cert={} receiver dropped, auto unsubscribe", cert_key);
+                self.unsubscribe(cert_key);
+                false
+            }
+        }
+    }
+
+    /// 暂停订阅, 之后实时分发的数据按mode缓存或记录重读位置; 消费组成员不支持暂停
+    pub fn pause(&self, cert_key: CertKeyT, mode: PauseMode) -> Result<()> {
+        match self.dispatcher_certs.get(&cert_key) {
+            None => bail!("cert key {} not subscribed", cert_key),
+            Some(cert) if cert.group.is_some() => bail!("group member {} can not be paused", cert_key),
+            Some(_) => {}
+        }
+        match self.paused.entry(cert_key) {
+            dashmap::mapref::entry::Entry::Occupied(_) => bail!("cert key {} already paused", cert_key),
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                entry.insert(PauseState::new(mode));
+            }
+        }
+        ::ftlog::info!("cert={} paused: {:?}", cert_key, mode);
         Ok(())
     }
 
+    /// 是否暂停中
+    pub fn is_paused(&self, cert_key: CertKeyT) -> bool {
+        self.paused.contains_key(&cert_key)
+    }
+
+    /// 读线程中恢复暂停的订阅: 发送缓存的数据, 需要重读时返回重读起点
+    pub(crate) fn resume(&self, cert_key: CertKeyT, ctx: &ReadContext) -> Option<u64> {
+        let (_, paused) = self.paused.remove(&cert_key)?;
+        ::ftlog::info!("cert={} resumed", cert_key);
+        match paused.resume()? {
+            Resumed::Buffered(rows) => {
+                if let Err(e) = self.send_to(&rows, ctx, cert_key) {
+                    ::ftlog::error!("resume send error: {:?}", e);
+                }
+                None
+            }
+            Resumed::Reread(offset) => Some(offset),
+        }
+    }
+
+    /// 订阅的溢出统计, 未订阅(或已断开)时为None
+    pub fn overflow_stats(&self, cert_key: CertKeyT) -> Option<OverflowStats> {
+        self.dispatcher_certs.get(&cert_key).map(|cert| cert.send_channel.stats())
+    }
+
     /// 取消订阅
     pub fn unsubscribe(&self, cert_key_t: CertKeyT) {
         if let Some((_, cert)) = self.dispatcher_certs.remove(&cert_key_t) {
             cert.is_running.store(false, Ordering::Relaxed); // 标记不可用
+            self.unindex_keys(cert_key_t, &cert.keys);
+            self.paused.remove(&cert_key_t);
+            if let Some(group) = &cert.group { // 未取走的数据重新分配给其他成员
+                let (_, closed) = group.leave(cert_key_t);
+                for closed in closed {
+                    self.handle_delivery(closed, Delivery::Closed);
+                }
+            }
             ::ftlog::debug!("unsubscribe: {}", cert_key_t)
             // 移除操作已经在上面完成，无需再次remove
         }
         // 不需要else分支，因为DashMap的remove方法在键不存在时返回None
     }
 
+    /// 是否存在该订阅者
+    pub fn contains(&self, cert_key: CertKeyT) -> bool {
+        self.dispatcher_certs.contains_key(&cert_key)
+    }
+
     /// 判断是否无订阅者
     pub fn no_subscriber(&self) -> bool {
         self.dispatcher_certs.is_empty()
     }
 
     /// 发送给单个订阅者
-    pub fn dispatch_single(&self, msgs: Vec<Result<T>>, cert_key: CertKeyT) -> anyhow::Result<()> {
-        ::ftlog::info!("ready dispatch:len={}",msgs.len());
-        if let Some(cert_data) = self.dispatcher_certs.get(&cert_key) {
-            if cert_data.is_running.load(Ordering::Relaxed) {
-                cert_data.send_channel.send(msgs)?;
+    pub fn dispatch_single(&self, rows: &[RowData<T>], ctx: &ReadContext, cert_key: CertKeyT) -> anyhow::Result<()> {
+        ::ftlog::info!("ready dispatch:len={}", rows.len());
+        let (send_channel, indexes) = match self.dispatcher_certs.get(&cert_key) {
+            Some(cert) if cert.is_running.load(Ordering::Relaxed) => {
+                let indexes = rows.iter().enumerate()
+                    .filter(|(_, row)| self.deliverable(row) && cert.accept(&row.data)) // 补发数据同样按订阅的过滤条件
+                    .map(|(index, _)| index)
+                    .collect::<Vec<_>>();
+                (cert.send_channel.clone(), indexes)
             }
+            _ => return Ok(()),
+        };
+        if let Some(mut paused) = self.paused.get_mut(&cert_key) { // 暂停中: 恢复时补发
+            paused.push(rows, &indexes, ctx.read_from);
+            return Ok(());
         }
-
+        let seqs = self.row_seqs(rows);
+        self.handle_delivery(cert_key, self.send_chunks(&send_channel, rows, &seqs, &indexes, ctx));
         Ok(())
     }
 
-    
+    /// 控制事件发给所有数据流订阅者
+    pub fn broadcast_event(&self, event: StreamItem<T>) {
+        let senders = self.dispatcher_certs.iter()
+            .filter(|cert| cert.is_running.load(Ordering::Relaxed) && cert.send_channel.receives_events())
+            .map(|cert| (cert.cert_key, cert.send_channel.clone()))
+            .collect::<Vec<_>>();
+        for (cert_key, sender) in senders { // 不持有map锁发送
+            self.handle_delivery(cert_key, sender.send_event(event.clone()));
+        }
+    }
+
+    /// 控制事件发给单个数据流订阅者
+    pub fn send_event(&self, cert_key: CertKeyT, event: StreamItem<T>) {
+        let sender = match self.dispatcher_certs.get(&cert_key).map(|cert| cert.send_channel.clone()) {
+            Some(sender) if sender.receives_events() => sender,
+            _ => return,
+        };
+        self.handle_delivery(cert_key, sender.send_event(event));
+    }
+
+    /// 按过滤条件发送给单个订阅者(包括未激活的), 返回是否有数据发出
+    pub fn send_to(&self, rows: &[RowData<T>], ctx: &ReadContext, cert_key: CertKeyT) -> anyhow::Result<bool> {
+        self.send_to_with(rows, ctx, cert_key, |_| {})
+    }
+
+    /// 同send_to; 有数据需要发送时, 发送前调用before_send, 参数为每个批次的结束下标
+    pub fn send_to_with<F: FnOnce(&[usize])>(&self, rows: &[RowData<T>], ctx: &ReadContext, cert_key: CertKeyT, before_send: F) -> anyhow::Result<bool> {
+        let (send_channel, indexes) = match self.dispatcher_certs.get(&cert_key) {
+            Some(cert) => {
+                let indexes = rows.iter().enumerate()
+                    .filter(|(_, row)| self.deliverable(row) && cert.accept(&row.data))
+                    .map(|(index, _)| index)
+                    .collect::<Vec<_>>();
+                (cert.send_channel.clone(), indexes)
+            }
+            None => return Ok(false),
+        };
+        if indexes.is_empty() {
+            return Ok(false);
+        }
+        if let Some(mut paused) = self.paused.get_mut(&cert_key) { // 暂停中: 恢复时补发
+            paused.push(rows, &indexes, ctx.read_from);
+            return Ok(false);
+        }
+        before_send(&self.chunk_ends(rows, &indexes));
+        let seqs = self.row_seqs(rows);
+        Ok(self.handle_delivery(cert_key, self.send_chunks(&send_channel, rows, &seqs, &indexes, ctx))) // 不持有map锁发送
+    }
+
+    // 按批次上限拆分后每个批次的结束下标, 与send_chunks的拆分一致
+    fn chunk_ends(&self, rows: &[RowData<T>], indexes: &[usize]) -> Vec<usize> {
+        self.batch.split(indexes.len(), |i| rows[indexes[i]].size).into_iter()
+            .filter(|range| !range.is_empty())
+            .map(|range| indexes[range.end - 1] + 1)
+            .collect()
+    }
+
+    // 按批次上限拆分后依次发送; 任一批次断开时不再发送后续批次
+    fn send_chunks(&self, send_channel: &CertSender<T>, rows: &[RowData<T>], seqs: &[u64], indexes: &[usize], ctx: &ReadContext) -> Delivery {
+        let mut result = Delivery::Sent;
+        for range in self.batch.split(indexes.len(), |i| rows[indexes[i]].size) {
+            match send_channel.send_rows(rows, seqs, &indexes[range], ctx) {
+                Delivery::Sent => {}
+                Delivery::Dropped => result = Delivery::Dropped,
+                delivery => return delivery,
+            }
+        }
+        result
+    }
+
 }
 
 
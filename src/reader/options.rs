@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use super::checkpoint::{CheckpointStore, FileCheckpointStore};
+use super::batching::BatchLimits;
+
+/*
+ReaderOptions: reader创建参数(可选功能), 仅在reader首次创建时生效
+*/
+#[derive(Clone, Default)]
+pub struct ReaderOptions {
+    pub checkpoint_store: Option<Arc<dyn CheckpointStore>>, // 读取位置持久化(仅增量读生效)
+    pub dead_letter_capacity: Option<usize>, // 死信通道容量, 设置后错误行不再发给订阅者
+    pub quarantine_file: Option<PathBuf>, // 错误行隔离文件, 设置后错误行不再发给订阅者
+    pub heartbeat_interval: Option<Duration>, // 数据流订阅者的心跳间隔
+    pub retention_rows: Option<usize>, // 内存保留的最大行数(仅增量读生效)
+    pub retention_bytes: Option<usize>, // 内存保留的最大字节数, 按原始记录长度估算(仅增量读生效)
+    pub batch_rows: Option<usize>, // 单次发送的最多行数
+    pub batch_bytes: Option<usize>, // 单次发送的最多字节数, 按原始记录长度估算
+    pub linger: Option<Duration>, // 收到文件变动通知后等待该时间再读取
+}
+
+impl ReaderOptions {
+    /// 使用自定义存储持久化读取位置
+    pub fn with_checkpoint_store(mut self, store: Arc<dyn CheckpointStore>) -> Self {
+        self.checkpoint_store = Some(store);
+        self
+    }
+
+    /// 使用文件存储持久化读取位置
+    pub fn with_file_checkpoint(self, dir: PathBuf) -> Result<Self> {
+        Ok(self.with_checkpoint_store(Arc::new(FileCheckpointStore::new(dir)?)))
+    }
+
+    /// 错误行发到死信通道(SubsReader::dead_letters获取)
+    pub fn with_dead_letter(mut self, capacity: usize) -> Self {
+        self.dead_letter_capacity = Some(capacity);
+        self
+    }
+
+    /// 向数据流订阅者(subscribe_stream)定期推送心跳
+    pub fn with_heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval);
+        self
+    }
+
+    /// 内存保留最近读取的rows行, 新订阅者优先从内存补发
+    pub fn with_retention_rows(mut self, rows: usize) -> Self {
+        self.retention_rows = Some(rows);
+        self
+    }
+
+    /// 内存保留最近读取的约bytes字节数据, 可与with_retention_rows同时使用(先达到的上限生效)
+    pub fn with_retention_bytes(mut self, bytes: usize) -> Self {
+        self.retention_bytes = Some(bytes);
+        self
+    }
+
+    /// 单次发送最多rows行, 补发等大量数据拆分为多个批次依次发送
+    pub fn with_batch_rows(mut self, rows: usize) -> Self {
+        self.batch_rows = Some(rows);
+        self
+    }
+
+    /// 单次发送最多约bytes字节, 可与with_batch_rows同时使用(先达到的上限生效)
+    pub fn with_batch_bytes(mut self, bytes: usize) -> Self {
+        self.batch_bytes = Some(bytes);
+        self
+    }
+
+    /// 收到文件变动通知后等待linger再读取, 频繁的小写入合并为较少的批次; 数据延迟相应增加
+    pub fn with_linger(mut self, linger: Duration) -> Self {
+        self.linger = Some(linger);
+        self
+    }
+
+    /// 分发的批次上限
+    pub fn batch_limits(&self) -> BatchLimits {
+        BatchLimits { max_rows: self.batch_rows, max_bytes: self.batch_bytes, linger: self.linger }
+    }
+
+    /// 错误行追加到隔离文件
+    pub fn with_quarantine_file(mut self, path: PathBuf) -> Self {
+        self.quarantine_file = Some(path);
+        self
+    }
+}
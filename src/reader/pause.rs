@@ -0,0 +1,62 @@
+use super::envelope::RowData;
+
+/*
+PauseMode: 暂停订阅期间的数据处理方式
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseMode {
+    Buffer(usize), // 内存中缓存最多N行, 恢复时先发送; 超出后改为从暂停期间的读取起点重读
+    Reread, // 不缓存, 记录暂停期间的读取起点, 恢复时从该位置重读到当前读取位置
+}
+
+/*
+PauseState: 单个订阅暂停期间未发送的数据, 实时分发/补发/回放均先经过这里
+重读起点取暂停期间各批次读取起点的最小值, 不依赖行的偏移(补发的错误行等没有偏移)
+*/
+pub(crate) struct PauseState<T> {
+    mode: PauseMode,
+    rows: Vec<RowData<T>>, // 缓存的行
+    read_from: Option<u64>, // 暂停期间各批次读取起点的最小值(CSV字节偏移/DBF记录序号)
+    reread: bool, // 超出缓存上限或Reread模式, 不再缓存
+}
+
+/// 恢复时需要补发的数据
+pub(crate) enum Resumed<T> {
+    Buffered(Vec<RowData<T>>),
+    Reread(u64),
+}
+
+impl<T: Clone> PauseState<T> {
+    pub fn new(mode: PauseMode) -> Self {
+        Self { mode, rows: vec![], read_from: None, reread: false }
+    }
+
+    /// 记录本批次该订阅的行(indexes为rows中的下标), read_from为本批次的读取起点, 未知时从头重读
+    pub fn push(&mut self, rows: &[RowData<T>], indexes: &[usize], read_from: Option<u64>) {
+        if indexes.is_empty() {
+            return;
+        }
+        let read_from = read_from.unwrap_or(0);
+        self.read_from = Some(self.read_from.map_or(read_from, |pos| pos.min(read_from)));
+        if self.reread {
+            return;
+        }
+        match self.mode {
+            PauseMode::Buffer(limit) if self.rows.len() + indexes.len() <= limit => {
+                self.rows.extend(indexes.iter().map(|i| rows[*i].clone()));
+            }
+            _ => { // 超出缓存上限, 从暂停期间的读取起点重读
+                self.reread = true;
+                self.rows.clear();
+            }
+        }
+    }
+
+    pub fn resume(self) -> Option<Resumed<T>> {
+        match self.read_from {
+            Some(read_from) if self.reread => Some(Resumed::Reread(read_from)),
+            _ if self.rows.is_empty() => None,
+            _ => Some(Resumed::Buffered(self.rows)),
+        }
+    }
+}
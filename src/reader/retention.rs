@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use anyhow::Result;
+use super::envelope::RowData;
+
+/*
+RetentionBuffer: 保留最近读取的已解析行, 新订阅者的补发优先从内存读取
+保留的行连续覆盖[start_pos, end_pos), 位置含义与SubsReader.seek_pos一致; 超出行数/字节数上限时丢弃最早的行
+除统计外只在读线程中调用
+*/
+pub struct RetentionBuffer<T> {
+    max_rows: Option<usize>, // 最多保留行数
+    max_bytes: Option<usize>, // 最多保留字节数(按原始记录长度估算)
+    inner: Mutex<Retained<T>>,
+}
+
+struct Retained<T> {
+    rows: VecDeque<(RowData<T>, usize)>, // (行, 估算字节数)
+    bytes: usize,
+    start_pos: u64, // 覆盖起点
+    end_pos: u64, // 覆盖终点
+}
+
+impl<T: Clone> RetentionBuffer<T> {
+    pub fn new(max_rows: Option<usize>, max_bytes: Option<usize>) -> Self {
+        Self {
+            max_rows,
+            max_bytes,
+            inner: Mutex::new(Retained { rows: VecDeque::new(), bytes: 0, start_pos: 0, end_pos: 0 }),
+        }
+    }
+
+    /// 是否启用(配置了行数或字节数上限)
+    pub fn enabled(&self) -> bool {
+        self.max_rows.is_some() || self.max_bytes.is_some()
+    }
+
+    /// 保留的行数
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 保留的估算字节数
+    pub fn bytes(&self) -> usize {
+        self.inner.lock().unwrap().bytes
+    }
+
+    /// 内存覆盖的位置区间[start, end)
+    pub fn range(&self) -> (u64, u64) {
+        let inner = self.inner.lock().unwrap();
+        (inner.start_pos, inner.end_pos)
+    }
+
+    /// 追加从begin_pos读到end_pos的行; 与已保留区间不连续时丢弃已保留的行
+    /// bytes_per_pos: 每个位置单位对应的字节数(CSV为1, DBF为记录长度)
+    pub(crate) fn push(&self, rows: &[RowData<T>], begin_pos: u64, end_pos: u64, bytes_per_pos: u64) {
+        if !self.enabled() || end_pos <= begin_pos {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if inner.end_pos != begin_pos {
+            inner.reset(begin_pos);
+        }
+        for (index, row) in rows.iter().enumerate() {
+            let next_pos = rows.get(index + 1).map(|next| next.offset).unwrap_or(end_pos);
+            let size = (next_pos.saturating_sub(row.offset) * bytes_per_pos) as usize;
+            inner.rows.push_back((row.clone(), size));
+            inner.bytes += size;
+        }
+        inner.end_pos = end_pos;
+        while inner.rows.len() > self.max_rows.unwrap_or(usize::MAX) || inner.bytes > self.max_bytes.unwrap_or(usize::MAX) {
+            let Some((_, size)) = inner.rows.pop_front() else {
+                break;
+            };
+            inner.bytes -= size;
+            inner.start_pos = inner.rows.front().map(|(row, _)| row.offset).unwrap_or(inner.end_pos);
+        }
+    }
+
+    /// 文件被截断/替换, 丢弃保留的行
+    pub(crate) fn clear(&self, pos: u64) {
+        self.inner.lock().unwrap().reset(pos);
+    }
+
+    /// 读取[begin, end)的行: 内存覆盖的部分从内存读取, 其余部分调用read_disk从文件读取
+    pub(crate) fn read<R>(&self, begin: u64, end: u64, read_disk: R) -> Result<Vec<RowData<T>>>
+    where
+        R: Fn(u64, u64) -> Result<Vec<RowData<T>>>,
+    {
+        let (disk_end, memory_rows) = {
+            let inner = self.inner.lock().unwrap();
+            if !self.enabled() || end > inner.end_pos || end <= inner.start_pos || inner.rows.is_empty() {
+                (end, vec![])
+            } else {
+                let memory_rows = inner.rows.iter()
+                    .filter(|(row, _)| row.offset >= begin && row.offset < end)
+                    .map(|(row, _)| row.clone())
+                    .collect::<Vec<_>>();
+                (inner.start_pos.max(begin), memory_rows)
+            }
+        };
+        if disk_end == begin {
+            ::ftlog::debug!("read [{}, {}) from retention buffer", begin, end);
+            return Ok(memory_rows);
+        }
+        ::ftlog::debug!("read [{}, {}) from disk, [{}, {}) from retention buffer", begin, disk_end, disk_end, end);
+        let mut rows = read_disk(begin, disk_end)?;
+        rows.extend(memory_rows);
+        Ok(rows)
+    }
+}
+
+impl<T> Retained<T> {
+    fn reset(&mut self, pos: u64) {
+        self.rows.clear();
+        self.bytes = 0;
+        self.start_pos = pos;
+        self.end_pos = pos;
+    }
+}
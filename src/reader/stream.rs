@@ -0,0 +1,48 @@
+use std::time::{Duration, Instant};
+use crate::common::model::FileIdentity;
+use super::error::ReadResult;
+
+/*
+StreamItem: subscribe_stream订阅时的推送项, 数据批次与控制事件按发生顺序推送
+*/
+#[derive(Debug, Clone)]
+pub enum StreamItem<T> {
+    Data(Vec<ReadResult<T>>), // 数据批次
+    FileReset { identity: FileIdentity }, // 文件被截断, 之后从头重新推送
+    FileRotated { old: FileIdentity, new: FileIdentity }, // 文件被替换为新文件, 之后从头推送新文件
+    SchemaChanged { fields: Vec<String> }, // 表头(CSV)/字段(DBF)变化
+    ReaderStopped, // reader已停止, 之后不再推送
+    CaughtUp { pos: u64 }, // 历史数据(注册前数据/回放)推送完成, pos为当前读取位置
+    Heartbeat { time: u64, pos: u64 }, // 心跳(ms), pos为当前读取位置
+    Disconnected, // 处理过慢, 按溢出策略(OverflowPolicy::Disconnect)断开, 之后不再推送
+}
+
+/*
+HeartbeatTimer: 读线程内的心跳计时, 不额外创建线程
+*/
+pub(crate) struct HeartbeatTimer {
+    interval: Option<Duration>,
+    last: Instant,
+}
+
+impl HeartbeatTimer {
+    pub(crate) fn new(interval: Option<Duration>) -> Self {
+        Self { interval, last: Instant::now() }
+    }
+
+    /// 等待事件的超时时间, 未配置心跳时为None(一直等待)
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        self.interval.map(|interval| interval.saturating_sub(self.last.elapsed()))
+    }
+
+    /// 是否到达心跳时间, 到达则重新计时
+    pub(crate) fn is_due(&mut self) -> bool {
+        match self.interval {
+            Some(interval) if self.last.elapsed() >= interval => {
+                self.last = Instant::now();
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -1,12 +1,26 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, atomic::{AtomicU64, Ordering}};
 use dashmap::DashMap;
 use serde::{Deserialize};
 use serde::de::DeserializeOwned;   
 use std::fs::File;
+use std::time::Duration;
 use std::sync::atomic::{AtomicBool};
 use crossbeam::channel::{bounded,Receiver, Sender};
-use super::msg_dispatcher::{MsgDispatcher, CertKeyT};
+use super::msg_dispatcher::{MsgDispatcher, CertKeyT, Filter, KeyFn, verify_filter};
+use super::options::ReaderOptions;
+use super::checkpoint::ReaderCheckpoint;
+use super::durable::DurableCursors;
+use super::retention::RetentionBuffer;
+use super::backpressure::{self, OverflowStats, SubscribeOptions};
+use super::subscription::{EnvelopeSubscription, ReaderCloser, StreamSubscription, Subscription};
+#[cfg(feature = "async")]
+use super::subscription::AsyncSubscription;
+use super::pause::PauseMode;
+use super::consumer_group::GroupOptions;
+use super::envelope::{ReadContext, RowData};
+use super::error::{ReadError, ReadResult};
+use super::dead_letter::{DeadLetter, QuarantineRecord, parse_quarantined};
 use crate::notify::GLOBAL_LISTENER;
 use crate::common::model::{*};
 use anyhow::{Result, bail};
@@ -15,8 +29,33 @@ pub type CsvReader<T> = SubsReader<T, CSV>;
 
 pub type DbfReader<T> = SubsReader<T, DBF>;
 
+/// 共享模式: 每行解析后包装一次Arc, 订阅者收到Vec<ReadResult<Arc<T>>>, 分发给多个订阅者时只增加引用计数;
+/// T无需实现Clone, 过滤条件/key提取函数的参数为&Arc<T>
+pub type SharedCsvReader<T> = SubsReader<Arc<T>, CSV>;
+
+pub type SharedDbfReader<T> = SubsReader<Arc<T>, DBF>;
+
+/// 回放起点
+pub enum ReplayFrom<T> {
+    ByteOffset(u64), // 文件字节偏移(DBF按文件头换算为记录序号), 从该位置之后的第一行开始
+    Row(u64), // 第N个数据行, 从0开始; 不含表头(CSV)及已删除记录(DBF), 解析失败的行同样计数
+    Time { time_of: fn(&T) -> u64, since: u64 }, // 第一条时间列 >= since 的行开始
+}
+
+/// 发送给读线程的读请求
+pub enum ReadRequest<T> {
+    FromHead, // 从当前位置读到文件尾部, 分发给所有订阅者(首次订阅时)
+    ToPos(CertKeyT, u64), // 从头读到指定位置, 仅发给单个订阅者
+    DurableActivate, // 补发并激活具名订阅
+    Replay(CertKeyT, ReplayFrom<T>), // 从回放起点读到当前位置, 仅发给单个订阅者
+    CaughtUp(CertKeyT), // 之前的读请求已处理, 通知数据流订阅者历史数据推送完成
+    Snapshot(CertKeyT), // 从头读到当前位置发给该订阅者后激活, 之后接收实时数据
+    Resume(CertKeyT), // 恢复暂停的订阅, 补发暂停期间的数据
+}
+
 pub trait ReadRunner {
     fn run(& self);
+    fn field_names(&self) -> Result<csv::StringRecord>; // 来源文件的字段名(CSV表头/DBF字段), 用于解析隔离记录
 }
 
 pub struct SubsReader<T: DeserializeOwned + Send + Sync + Clone + 'static,  F: FileType> {
@@ -28,92 +67,351 @@ pub struct SubsReader<T: DeserializeOwned + Send + Sync + Clone + 'static,  F: F
     pub msg_dispatcher:Arc<MsgDispatcher<T>>, // 消息分发器
     pub is_running: Arc<AtomicBool>, // 控制扫单线程运行
     pub notify_meta: NotifyMeta,
-    pub inner_chan: (Sender<(CertKeyT, u64)>, Receiver<(CertKeyT, u64)>), // 内部通信通道
+    pub inner_chan: (Sender<ReadRequest<T>>, Receiver<ReadRequest<T>>), // 内部通信通道
     register_before_pos: DashMap<CertKeyT, u64>, // 记录具体chan注册时的文件大小
     read_from_head: Arc<AtomicBool>, // 是否 已经文件头开始读过
+    pub options: ReaderOptions, // 创建参数
+    pub checkpoint: Option<ReaderCheckpoint>, // 读取位置持久化
+    pub durable: Arc<DurableCursors>, // 具名订阅的消费位置
+    pub retention: Arc<RetentionBuffer<T>>, // 最近读取的行, 用于新订阅者补发
     _phantom: std::marker::PhantomData<F>, // 占位防止编译出错
 }
 
 
 impl<T: DeserializeOwned + Send + Sync + Clone + 'static, F: FileType> SubsReader<T, F> {
     pub fn new(file_path: PathBuf, is_increment: bool, enc: EncType) -> Result<Self> {
+        Self::with_options(file_path, is_increment, enc, ReaderOptions::default())
+    }
+
+    pub fn with_options(file_path: PathBuf, is_increment: bool, enc: EncType, options: ReaderOptions) -> Result<Self> {
         let notify_meta = GLOBAL_LISTENER.add_watch(file_path.clone())?;
         ::ftlog::info!("[INIT_READER];FILE_TYPE={},INCREMENT={},FILE_PATH={},ENC_TYPE={}", F::file_type(), is_increment, file_path.display(), enc);
+        let checkpoint = match &options.checkpoint_store {
+            Some(store) if is_increment => Some(ReaderCheckpoint {
+                store: store.clone(),
+                key: Self::checkpoint_key(&file_path),
+                file_path: file_path.clone(),
+            }),
+            _ => None,
+        };
+        let init_seek_pos = checkpoint.as_ref().map(|c| c.load_seek_pos()).unwrap_or(0); // 文件身份一致则从checkpoint继续
+        let msg_dispatcher = match (options.dead_letter_capacity, &options.quarantine_file) {
+            (None, None) => MsgDispatcher::new(),
+            (capacity, quarantine_file) => MsgDispatcher::with_dead_letter(Arc::new(DeadLetter::new(capacity, quarantine_file.clone()))),
+        }.with_batch_limits(options.batch_limits());
+        let retention = Arc::new(RetentionBuffer::new(options.retention_rows, options.retention_bytes));
         Ok(Self {
             file_path,
             is_increment,
-            seek_pos: Arc::new(AtomicU64::new(0)),
+            seek_pos: Arc::new(AtomicU64::new(init_seek_pos)),
             enc_type: enc,
             fd: None,
-            msg_dispatcher: Arc::new(MsgDispatcher::new()),
+            msg_dispatcher: Arc::new(msg_dispatcher),
             is_running: Arc::new(AtomicBool::new(false)),
             notify_meta,
             inner_chan: bounded(4), // 第一次register时读取
             read_from_head: Arc::new(AtomicBool::new(false)),
             register_before_pos: DashMap::new(),
+            options,
+            checkpoint,
+            durable: Arc::new(DurableCursors::new()),
+            retention,
             _phantom: std::marker::PhantomData,
         })
     }
 
+    fn checkpoint_key(file_path: &Path) -> String {
+        format!("{}|{}", F::file_type(), file_path.display())
+    }
+
     /// 重置文件读取位置
     pub fn reset_seek_pos(&self) { // 严格同步
         self.seek_pos.store(0, Ordering::Release)
     }
 
-    /// 订阅 返回一个cert和chan
-    pub fn subscribe(&self, verify_data: &str, dispatcher_func: fn(&str, &T) -> bool) -> (CertKeyT, Receiver<Vec<Result<T>>>)
+    /// 订阅, 返回订阅句柄; 句柄drop时自动取消订阅, 也可调用close取消并获取结果
+    ///
+    /// 注册前数据与实时数据的衔接依赖时序, 可能重复或遗漏; 需要严格衔接时使用subscribe_snapshot
+    pub fn subscribe(&self, verify_data: &str, dispatcher_func: fn(&str, &T) -> bool) -> Subscription<T>
+    where
+        Self: ReadRunner,
+    {
+        self.subscribe_filter(verify_filter(verify_data, dispatcher_func))
+    }
+
+    /// 按过滤条件订阅, 过滤条件可捕获状态(如账户集合、共享配置); 其余行为与subscribe一致
+    pub fn subscribe_filter(&self, filter: Filter<T>) -> Subscription<T>
+    where
+        Self: ReadRunner,
+    {
+        self.subscribe_with(filter, SubscribeOptions::default())
+    }
+
+    /// 按过滤条件订阅, 指定通道容量及通道满时的处理方式(默认阻塞读线程);
+    /// 处理较慢的订阅者可选择丢弃/合并/断开, 避免阻塞同一文件的其他订阅者
+    pub fn subscribe_with(&self, filter: Filter<T>, options: SubscribeOptions) -> Subscription<T>
     where
         Self: ReadRunner,
     {
         // self.reset_seek_pos(); // 无需重置位置;通过其他逻辑单独弥补
-        let (send_chan, recv_chan) = bounded(16);
-        // 获取锁并调用方法
-        let cert_key = self.msg_dispatcher.get_cert_and_subscribe(verify_data, dispatcher_func, send_chan); // 需要在获取文件seek前执行
+        let (send_chan, recv_chan) = backpressure::channel(&options);
+        let cert_key = self.msg_dispatcher.get_cert();
+        self.msg_dispatcher.subscribe_filter(filter, send_chan, cert_key); // 需要在获取文件seek前执行
+        self.start_for_subscriber(cert_key);
+        Subscription::new(cert_key, recv_chan, self.closer())
+    }
+
+    /// 设置路由key提取函数(如 |data| data.trade_acc.as_str()), 只能设置一次
+    pub fn set_key_fn(&self, key_fn: KeyFn<T>) -> Result<()> {
+        self.msg_dispatcher.set_key_fn(key_fn)
+    }
+
+    /// 按key订阅: 只接收key在keys中的数据, 分发时每行提取一次key查表, 订阅者多时开销远小于逐个过滤;
+    /// 可与按过滤条件的订阅同时使用, 需要先调用set_key_fn
+    pub fn subscribe_keys(&self, keys: &[&str]) -> Result<Subscription<T>>
+    where
+        Self: ReadRunner,
+    {
+        self.subscribe_keys_with(keys, SubscribeOptions::default())
+    }
+
+    /// 按key订阅, 指定通道容量及通道满时的处理方式
+    pub fn subscribe_keys_with(&self, keys: &[&str], options: SubscribeOptions) -> Result<Subscription<T>>
+    where
+        Self: ReadRunner,
+    {
+        let (send_chan, recv_chan) = backpressure::channel(&options);
+        let cert_key = self.msg_dispatcher.get_cert();
+        self.msg_dispatcher.subscribe_keys(keys, send_chan, cert_key)?; // 需要在获取文件seek前执行
+        self.start_for_subscriber(cert_key);
+        Ok(Subscription::new(cert_key, recv_chan, self.closer()))
+    }
+
+    /// 更新订阅的校验数据, 从下一个分发批次开始生效; 不会重新补发注册前数据
+    pub fn update_verify(&self, cert_key: CertKeyT, verify_data: &str, dispatcher_func: fn(&str, &T) -> bool) -> Result<()> {
+        self.update_filter(cert_key, verify_filter(verify_data, dispatcher_func))
+    }
+
+    /// 更新订阅的过滤条件, 适用于各种订阅方式; 按key订阅的改为按过滤条件逐行判断
+    pub fn update_filter(&self, cert_key: CertKeyT, filter: Filter<T>) -> Result<()> {
+        self.msg_dispatcher.update_filter(cert_key, filter)
+    }
+
+    /// 更新订阅的key列表(替换原有的), 从下一个分发批次开始生效; 按过滤条件订阅的改为按key分发
+    pub fn update_keys(&self, cert_key: CertKeyT, keys: &[&str]) -> Result<()> {
+        self.msg_dispatcher.update_keys(cert_key, keys)
+    }
+
+    /// 创建竞争消费组: 满足过滤条件的每行只发给组内一个成员; 按partition提取的key(如订单号)分配成员,
+    /// 同一key的数据固定发给一个成员, 保持顺序
+    pub fn create_group(&self, name: &str, filter: Filter<T>, partition: KeyFn<T>) -> Result<()> {
+        self.msg_dispatcher.create_group(name, filter, partition)
+    }
+
+    /// 创建竞争消费组, 指定暂存行数上限及超出时的处理方式
+    pub fn create_group_with(&self, name: &str, filter: Filter<T>, partition: KeyFn<T>, options: GroupOptions) -> Result<()> {
+        self.msg_dispatcher.create_group_with(name, filter, partition, options)
+    }
+
+    /// 删除无成员的消费组
+    pub fn remove_group(&self, name: &str) -> Result<()> {
+        self.msg_dispatcher.remove_group(name)
+    }
+
+    /// 加入消费组, 返回订阅句柄; 不单独补发注册前数据, 由组内分配
+    ///
+    /// 成员退出(句柄close/drop)时通道中未取走的批次及其负责的key转给其他成员; detach后的接收端取消订阅时不转发;
+    /// 组内无成员或成员通道满时暂存, 之后按原顺序发送
+    pub fn join_group(&self, name: &str) -> Result<Subscription<T>>
+    where
+        Self: ReadRunner,
+    {
+        self.join_group_with(name, SubscribeOptions::default())
+    }
+
+    /// 加入消费组, 指定通道容量及通道满时的处理方式; 消费组投递不阻塞读线程, Block时通道满的行暂存在组内
+    pub fn join_group_with(&self, name: &str, options: SubscribeOptions) -> Result<Subscription<T>>
+    where
+        Self: ReadRunner,
+    {
+        let (send_chan, recv_chan) = backpressure::channel(&options);
+        let cert_key = self.msg_dispatcher.get_cert();
+        self.msg_dispatcher.join_group(name, send_chan.with_drain(&recv_chan), cert_key)?; // 退出(含detach后取消)时取回未取走的批次
+        self.start_reader();
+        Ok(Subscription::new(cert_key, recv_chan, self.closer()))
+    }
+
+    /// 订阅带来源信息(文件/偏移/行号/通知时间/序号)的数据, 其余行为与subscribe一致
+    pub fn subscribe_envelope(&self, verify_data: &str, dispatcher_func: fn(&str, &T) -> bool) -> EnvelopeSubscription<T>
+    where
+        Self: ReadRunner,
+    {
+        let (send_chan, recv_chan) = backpressure::channel(&SubscribeOptions::default());
+        let cert_key = self.msg_dispatcher.get_cert();
+        self.msg_dispatcher.subscribe_envelope(verify_filter(verify_data, dispatcher_func), send_chan, cert_key); // 需要在获取文件seek前执行
+        self.start_for_subscriber(cert_key);
+        Subscription::new(cert_key, recv_chan, self.closer())
+    }
+
+    /// 订阅数据及控制事件(文件重置/替换、字段变化、reader停止、历史数据推送完成、心跳), 按发生顺序推送
+    pub fn subscribe_stream(&self, verify_data: &str, dispatcher_func: fn(&str, &T) -> bool) -> StreamSubscription<T>
+    where
+        Self: ReadRunner,
+    {
+        self.subscribe_stream_with(verify_filter(verify_data, dispatcher_func), SubscribeOptions::default())
+    }
+
+    /// 按过滤条件订阅数据流, 指定通道容量及通道满时的处理方式; Disconnect断开前推送Disconnected
+    pub fn subscribe_stream_with(&self, filter: Filter<T>, options: SubscribeOptions) -> StreamSubscription<T>
+    where
+        Self: ReadRunner,
+    {
+        let (send_chan, recv_chan) = backpressure::channel(&options);
+        let cert_key = self.msg_dispatcher.get_cert();
+        self.msg_dispatcher.subscribe_stream(filter, send_chan, cert_key); // 需要在获取文件seek前执行
+        self.start_for_subscriber(cert_key);
+        let _ = self.inner_chan.0.send(ReadRequest::CaughtUp(cert_key)); // 排在补发请求之后
+        Subscription::new(cert_key, recv_chan, self.closer())
+    }
+
+    /// 异步订阅(需要async feature): 推送内容与subscribe_stream一致, 返回的接收端实现futures_core::Stream,
+    /// 读线程发送后直接唤醒等待的任务, 无需桥接线程; 取消订阅后Stream结束
+    #[cfg(feature = "async")]
+    pub fn subscribe_async(&self, verify_data: &str, dispatcher_func: fn(&str, &T) -> bool) -> AsyncSubscription<T>
+    where
+        Self: ReadRunner,
+    {
+        let (send_chan, recv_chan) = backpressure::async_channel(&SubscribeOptions::default());
+        let cert_key = self.msg_dispatcher.get_cert();
+        self.msg_dispatcher.subscribe_async(verify_filter(verify_data, dispatcher_func), send_chan, cert_key); // 需要在获取文件seek前执行
+        self.start_for_subscriber(cert_key);
+        let _ = self.inner_chan.0.send(ReadRequest::CaughtUp(cert_key)); // 排在补发请求之后
+        Subscription::new(cert_key, recv_chan, self.closer())
+    }
+
+    /// 快照订阅: 先收到[0, 当前读取位置)的快照, 之后的实时数据从快照结束位置开始, 不重复不遗漏
+    ///
+    /// 快照与激活都在读线程中执行, 期间读线程不会分发实时数据; 快照读取失败时以错误行告知订阅方
+    pub fn subscribe_snapshot(&self, verify_data: &str, dispatcher_func: fn(&str, &T) -> bool) -> Result<Subscription<T>>
+    where
+        Self: ReadRunner,
+    {
+        let (send_chan, recv_chan) = backpressure::channel(&SubscribeOptions::default());
+        let cert_key = self.msg_dispatcher.get_cert();
+        self.msg_dispatcher.subscribe_inactive(verify_filter(verify_data, dispatcher_func), send_chan, cert_key); // 由读线程发送快照后激活
+        self.start_reader();
+        self.inner_chan.0.send(ReadRequest::Snapshot(cert_key))?;
+        Ok(Subscription::new(cert_key, recv_chan, self.closer()))
+    }
+
+    // 启动读取线程并弥补注册之前的数据
+    fn start_for_subscriber(&self, cert_key: CertKeyT)
+    where
+        Self: ReadRunner,
+    {
         let current_pos = self.seek_pos.load(Ordering::Relaxed); // 记录当前位置(需要弥补数据)
+        self.register_before_pos.insert(cert_key, current_pos); // 存储用于后续查询
+        /*
+        NOTE:获取seek前, 通信chan已经插入, FromHead会将数据同步给所有chan
+        */
+        if !self.start_reader() && cfg!(feature = "before_register_data") && current_pos != 0 { // 如果为0, 代表reader在处理FromHead, 会通过FromHead收到数据
+            ::ftlog::info!("read from last seek pos for new register");
+            let _ = self.inner_chan.0.send(ReadRequest::ToPos(cert_key, current_pos)); // 从头读到当前文件位置
+        }
+    }
+
+    // 第一次运行时启动扫单线程, 并从头读到文件尾部(reader仅会从头读一次); 返回本次是否发起了从头读取
+    fn start_reader(&self) -> bool
+    where
+        Self: ReadRunner,
+    {
         if self.is_running.compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
             ::ftlog::info!("start reader loop");
-            self.run(); // 第一次运行,则启动扫单线程
+            self.run();
         }
-        self.register_before_pos.insert(cert_key, current_pos); // 存储用于后续查询
         #[cfg(feature = "before_register_data")]
-        {   // 弥补注册之前数据,使其可以增量读
-            if self.read_from_head.compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
-                ::ftlog::info!("read from zero seek pos");
-                self.read_from_head.store(true, Ordering::Relaxed); // reader仅会从头读一次
-                let _ = self.inner_chan.0.send((cert_key, READ_FROM_HEAD_FLAG)); // 从头开始读到文件尾部
-            } else {
-                /*
-                NOTE:获取seek前, 通信chan已经插入, READ_FROM_HEAD_FLAG会将数据同步给所有chan
-                */
-                ::ftlog::info!("read from last seek pos for new register");
-                if current_pos != 0 { 
-                    let _ = self.inner_chan.0.send((cert_key, current_pos)); // 从头读到当前文件位置
-                } // 如果为0, 代表reader在处理READ_FROM_HEAD_FLAG, 会通过READ_FROM_HEAD_FLAG受到数据
-                
-            }
-            
+        if self.read_from_head.compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+            ::ftlog::info!("read from zero seek pos");
+            let _ = self.inner_chan.0.send(ReadRequest::FromHead);
+            return true;
         }
-        
-        (cert_key, recv_chan)
+        false
     }
 
 
-    /// 取消订阅
-    pub fn unsubscribe(&self, cert_key: CertKeyT) -> anyhow::Result<()> {
-        {
-            self.msg_dispatcher.unsubscribe(cert_key);
+    /// 具名订阅: 消费位置按name持久化, 订阅方处理完每个批次后调用ack推进;
+    /// 重启后从最后一次ack的位置继续推送(至少一次), 需要增量读且配置了checkpoint_store
+    pub fn subscribe_durable(&self, name: &str, verify_data: &str, dispatcher_func: fn(&str, &T) -> bool) -> Result<Subscription<T>>
+    where
+        Self: ReadRunner,
+    {
+        let store = match &self.options.checkpoint_store {
+            Some(store) if self.is_increment => store.clone(),
+            _ => bail!("durable subscription requires increment reader with checkpoint store"),
+        };
+        let checkpoint = ReaderCheckpoint {
+            store,
+            key: format!("{}#{}", Self::checkpoint_key(&self.file_path), name),
+            file_path: self.file_path.clone(),
+        };
+        let cert_key = self.msg_dispatcher.get_cert();
+        let start_pos = self.durable.register(cert_key, name, checkpoint)?;
+        let (send_chan, recv_chan) = backpressure::channel(&SubscribeOptions::default());
+        self.msg_dispatcher.subscribe_inactive(verify_filter(verify_data, dispatcher_func), send_chan, cert_key); // 由读线程补发后激活
+        ::ftlog::info!("{} durable subscribe {} from {}", self.file_path.display(), name, start_pos);
+        self.start_reader();
+        self.inner_chan.0.send(ReadRequest::DurableActivate)?;
+        Ok(Subscription::new(cert_key, recv_chan, self.closer()))
+    }
+
+    /// 订阅的溢出统计(丢弃/合并的批次数), 未订阅或已断开时为None
+    pub fn overflow_stats(&self, cert_key: CertKeyT) -> Option<OverflowStats> {
+        self.msg_dispatcher.overflow_stats(cert_key)
+    }
+
+    /// 暂停订阅, 不影响其他订阅者; 暂停期间的数据按mode缓存或记录重读位置, resume后补发, 不丢数据
+    ///
+    /// 具名订阅通过ack控制消费进度, 不支持暂停
+    pub fn pause(&self, cert_key: CertKeyT, mode: PauseMode) -> Result<()> {
+        if self.durable.acked_pos(cert_key).is_some() {
+            bail!("durable subscription {} can not be paused", cert_key);
         }
+        self.msg_dispatcher.pause(cert_key, mode)
+    }
 
-        let no_subscriber = {
-            self.msg_dispatcher.no_subscriber()
-        };
+    /// 恢复暂停的订阅: 在读线程中先补发暂停期间的数据(缓存或从记录位置重读到当前读取位置), 之后接收实时数据
+    pub fn resume(&self, cert_key: CertKeyT) -> Result<()> {
+        if !self.msg_dispatcher.is_paused(cert_key) {
+            bail!("cert key {} not paused", cert_key);
+        }
+        self.inner_chan.0.send(ReadRequest::Resume(cert_key))?;
+        Ok(())
+    }
+
+    /// 确认具名订阅最早一个未确认的批次, 返回已确认位置
+    pub fn ack(&self, cert_key: CertKeyT) -> Result<u64> {
+        self.durable.ack(cert_key)
+    }
+
+    /// 取消订阅, 无人订阅时停止扫单; 该订阅不存在(已取消或已自动移除)时返回Err
+    pub fn unsubscribe(&self, cert_key: CertKeyT) -> anyhow::Result<()> {
+        self.closer().unsubscribe(cert_key)
+    }
 
-        if no_subscriber { //无人订阅, 则停止扫单
-            self.is_running.store(false, Ordering::SeqCst);
-            ::ftlog::info!("stop scan file");
-            return Ok(());
+    /// 停止扫单, 数据流订阅者收到ReaderStopped
+    pub fn stop(&self) {
+        self.closer().stop()
+    }
+
+    // 供订阅句柄取消订阅
+    fn closer(&self) -> ReaderCloser<T> {
+        ReaderCloser {
+            file_path: self.file_path.clone(),
+            msg_dispatcher: self.msg_dispatcher.clone(),
+            durable: self.durable.clone(),
+            is_running: self.is_running.clone(),
+            notify_sender: self.notify_meta.sender.clone(),
         }
-       bail!("Failed to get subscriber")
     }
 
     pub fn empty(&self) -> anyhow::Result<bool> {
@@ -124,7 +422,7 @@ impl<T: DeserializeOwned + Send + Sync + Clone + 'static, F: FileType> SubsReade
     pub fn get_register_before_data(&self, cert_key: CertKeyT) -> Result<()> {
         ::ftlog::info!("{} reading register before data", cert_key);
         if let Some(seek_pos) = self.register_before_pos.get(&cert_key) {
-            self.inner_chan.0.send((cert_key, *seek_pos))?; // 异步读取之前的数据
+            self.inner_chan.0.send(ReadRequest::ToPos(cert_key, *seek_pos))?; // 异步读取之前的数据
         } else {
             ::ftlog::error!("cert key {} no register before pos", cert_key);
             bail!("cert key no register")
@@ -135,7 +433,43 @@ impl<T: DeserializeOwned + Send + Sync + Clone + 'static, F: FileType> SubsReade
     pub fn get_all_data(&self, cert_key: CertKeyT) -> Result<()> {
         ::ftlog::info!("get all data for cert key {}", cert_key);
         let pos = self.seek_pos.load(Ordering::Relaxed);
-        self.inner_chan.0.send((cert_key, pos))?; // 异步读取数据
+        self.inner_chan.0.send(ReadRequest::ToPos(cert_key, pos))?; // 异步读取数据
+        Ok(())
+    }
+
+    /// 死信接收端, 需要配置ReaderOptions::with_dead_letter
+    pub fn dead_letters(&self) -> Option<Receiver<ReadError>> {
+        self.msg_dispatcher.dead_letter().and_then(|dead_letter| dead_letter.receiver())
+    }
+
+    /// 重新分发隔离文件中(修正后)的记录, 仅处理来源为本文件的记录, 返回解析成功的条数;
+    /// 仍然出错的记录再次进入死信
+    pub fn redeliver(&self, records: &[QuarantineRecord]) -> Result<usize>
+    where
+        Self: ReadRunner,
+    {
+        let source = self.file_path.display().to_string();
+        let records = records.iter().filter(|r| r.file_path == source).cloned().collect::<Vec<_>>();
+        if records.is_empty() {
+            return Ok(0);
+        }
+        let shared_path = Arc::new(self.file_path.clone());
+        let rows = parse_quarantined::<T>(&records, &self.field_names()?, &shared_path);
+        ::ftlog::info!("{} redeliver {} quarantined records", self.file_path.display(), rows.len());
+        self.msg_dispatcher.dispatch_rows(&rows, &ReadContext::now(&shared_path))?;
+        Ok(rows.iter().filter(|row| row.data.is_ok()).count())
+    }
+
+    /// 从指定起点回放到当前读取位置, 仅发给该订阅者; 在读线程中执行, 不影响实时数据的分发顺序
+    ///
+    /// 回放数据同样按订阅的过滤条件发送, 回放批次不参与具名订阅的ack; 数据流订阅者回放完成后收到CaughtUp
+    pub fn replay(&self, cert_key: CertKeyT, from: ReplayFrom<T>) -> Result<()> {
+        if !self.msg_dispatcher.contains(cert_key) {
+            bail!("cert key {} not subscribed", cert_key);
+        }
+        ::ftlog::info!("{} replay for cert key {}", self.file_path.display(), cert_key);
+        self.inner_chan.0.send(ReadRequest::Replay(cert_key, from))?;
+        self.inner_chan.0.send(ReadRequest::CaughtUp(cert_key))?;
         Ok(())
     }
 
@@ -145,7 +479,51 @@ impl<T: DeserializeOwned + Send + Sync + Clone + 'static, F: FileType> SubsReade
 // 分发数据
 pub fn dispatch_data<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static>(
     msg_dispatcher: &Arc<MsgDispatcher<T>>,
-    data_list: Vec<anyhow::Result<T>>
+    data_list: Vec<ReadResult<T>>
 ) -> anyhow::Result<()> {
     msg_dispatcher.dispatch(data_list)
-}
\ No newline at end of file
+}
+
+// 读线程中处理快照订阅: 发送快照后激活, 激活前的实时数据不会发给该订阅者
+pub(crate) fn send_snapshot<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static>(
+    msg_dispatcher: &MsgDispatcher<T>,
+    cert_key: CertKeyT,
+    snapshot: Result<Vec<RowData<T>>>,
+    ctx: &ReadContext,
+) {
+    let rows = snapshot.unwrap_or_else(|e| {
+        ::ftlog::error!("{} snapshot read error: {:?}", cert_key, e);
+        vec![RowData::detached(Err(ReadError::from(e)))] // 告知订阅方快照不完整
+    });
+    ::ftlog::info!("{} snapshot data len: {}", cert_key, rows.len());
+    if let Err(e) = msg_dispatcher.send_to(&rows, ctx, cert_key) {
+        ::ftlog::error!("snapshot send error: {:?}", e);
+    }
+    msg_dispatcher.activate(cert_key);
+}
+
+// 读线程中调用: 配置了linger时等待后再读取, 期间的文件变动通知合并为一次读取
+pub(crate) fn linger_notify(linger: Option<Duration>, notify_receiver: &Receiver<NotifyEventData>) {
+    if let Some(linger) = linger {
+        std::thread::sleep(linger);
+        let merged = notify_receiver.try_iter().count(); // StopEvent被合并时由is_running退出
+        ::ftlog::trace!("linger {:?}, merged {} notify", linger, merged);
+    }
+}
+
+// 读线程中调用: 订阅者全部被移除(接收端关闭后自动取消订阅)时按无人订阅停止, 返回是否停止;
+// 停止时恰有新订阅的, 保证只有一个读线程继续运行
+pub(crate) fn stop_if_no_subscriber<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static>(
+    msg_dispatcher: &MsgDispatcher<T>,
+    is_running: &AtomicBool,
+) -> bool {
+    if !msg_dispatcher.no_subscriber() {
+        return false;
+    }
+    is_running.store(false, Ordering::SeqCst);
+    if msg_dispatcher.no_subscriber() {
+        ::ftlog::info!("no subscriber, stop scan file");
+        return true;
+    }
+    is_running.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() // 新订阅已启动读线程时本线程退出
+}
@@ -0,0 +1,164 @@
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crossbeam::channel::Sender;
+use serde::Deserialize;
+use anyhow::{Result, bail};
+use super::msg_dispatcher::{MsgDispatcher, CertKeyT, Filter, verify_filter};
+use super::durable::DurableCursors;
+use super::backpressure::OverflowReceiver;
+use super::envelope::Envelope;
+use super::stream::StreamItem;
+#[cfg(feature = "async")]
+use super::async_stream::AsyncReceiver;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
+#[cfg(feature = "async")]
+use futures_core::Stream;
+use super::error::ReadResult;
+use crate::common::model::{NotifyEvent, NotifyEventData};
+use crate::common::timer::get_coarse_timestamp_ms;
+
+/*
+ReaderCloser: 取消订阅/停止扫单所需的reader状态, 不持有reader本身
+*/
+#[derive(Clone)]
+pub(crate) struct ReaderCloser<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> {
+    pub file_path: PathBuf,
+    pub msg_dispatcher: Arc<MsgDispatcher<T>>,
+    pub durable: Arc<DurableCursors>,
+    pub is_running: Arc<AtomicBool>,
+    pub notify_sender: Sender<NotifyEventData>,
+}
+
+impl<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static> ReaderCloser<T> {
+    /// 取消订阅, 无人订阅时停止扫单; 该订阅不存在(已取消或已自动移除)时返回Err
+    pub fn unsubscribe(&self, cert_key: CertKeyT) -> Result<()> {
+        let subscribed = self.msg_dispatcher.contains(cert_key);
+        self.msg_dispatcher.unsubscribe(cert_key);
+        self.durable.remove(cert_key);
+        if self.msg_dispatcher.no_subscriber() { //无人订阅, 则停止扫单
+            self.stop();
+        }
+        if !subscribed {
+            bail!("cert key {} not subscribed", cert_key);
+        }
+        Ok(())
+    }
+
+    /// 停止扫单, 数据流订阅者收到ReaderStopped
+    pub fn stop(&self) {
+        self.is_running.store(false, Ordering::SeqCst);
+        let _ = self.notify_sender.send(NotifyEventData { // 唤醒读线程
+            event: NotifyEvent::StopEvent,
+            last_notify_time: get_coarse_timestamp_ms(),
+        });
+        ::ftlog::info!("{} stop scan file", self.file_path.display());
+    }
+}
+
+/*
+Subscription: 订阅句柄, 持有接收端; drop时自动取消订阅, 无需记录cert_key/路径/类型
+可直接当作接收端使用(Receiver的recv/recv_timeout/Select, 或异步订阅的Stream), R为接收端类型
+*/
+pub struct Subscription<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static, R = OverflowReceiver<Vec<ReadResult<T>>>> {
+    cert_key: CertKeyT,
+    receiver: Option<R>, // 只在detach时取出
+    closer: Option<ReaderCloser<T>>, // 已关闭或detach后为None
+}
+
+/// 带来源信息的订阅句柄
+pub type EnvelopeSubscription<T> = Subscription<T, OverflowReceiver<Vec<ReadResult<Envelope<T>>>>>;
+
+/// 数据流订阅句柄, 推送数据及控制事件
+pub type StreamSubscription<T> = Subscription<T, OverflowReceiver<StreamItem<T>>>;
+
+/// 异步订阅句柄, 实现futures_core::Stream
+#[cfg(feature = "async")]
+pub type AsyncSubscription<T> = Subscription<T, AsyncReceiver<StreamItem<T>>>;
+
+impl<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static, R> Subscription<T, R> {
+    pub(crate) fn new(cert_key: CertKeyT, receiver: R, closer: ReaderCloser<T>) -> Self {
+        Self { cert_key, receiver: Some(receiver), closer: Some(closer) }
+    }
+
+    /// 订阅凭证
+    pub fn cert_key(&self) -> CertKeyT {
+        self.cert_key
+    }
+
+    /// 接收端
+    pub fn receiver(&self) -> &R {
+        self
+    }
+
+    /// 更新校验数据, 从下一个分发批次开始生效, 不会重新补发注册前数据
+    pub fn update_verify(&self, verify_data: &str, dispatcher_func: fn(&str, &T) -> bool) -> Result<()> {
+        self.update_filter(verify_filter(verify_data, dispatcher_func))
+    }
+
+    /// 更新过滤条件, 从下一个分发批次开始生效
+    pub fn update_filter(&self, filter: Filter<T>) -> Result<()> {
+        match &self.closer {
+            Some(closer) => closer.msg_dispatcher.update_filter(self.cert_key, filter),
+            None => bail!("subscription closed"),
+        }
+    }
+
+    /// 更新key列表(替换原有的), 从下一个分发批次开始生效; 需要reader已设置key提取函数
+    pub fn update_keys(&self, keys: &[&str]) -> Result<()> {
+        match &self.closer {
+            Some(closer) => closer.msg_dispatcher.update_keys(self.cert_key, keys),
+            None => bail!("subscription closed"),
+        }
+    }
+
+    /// 取消订阅并返回结果; 该订阅已被移除(如按溢出策略断开)时返回Err
+    pub fn close(mut self) -> Result<()> {
+        match self.closer.take() {
+            Some(closer) => closer.unsubscribe(self.cert_key),
+            None => Ok(()),
+        }
+    }
+
+    /// 不再自动取消订阅, 返回凭证和接收端, 之后由调用方通过unsubscribe取消
+    pub fn detach(mut self) -> (CertKeyT, R) {
+        self.closer = None;
+        (self.cert_key, self.receiver.take().expect("subscription receiver"))
+    }
+}
+
+impl<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static, R> Deref for Subscription<T, R> {
+    type Target = R;
+
+    fn deref(&self) -> &Self::Target {
+        self.receiver.as_ref().expect("subscription receiver")
+    }
+}
+
+impl<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static, R> DerefMut for Subscription<T, R> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.receiver.as_mut().expect("subscription receiver")
+    }
+}
+
+// 异步订阅句柄可直接作为Stream使用
+#[cfg(feature = "async")]
+impl<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static, R: Stream + Unpin> Stream for Subscription<T, R> {
+    type Item = R::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut **self.get_mut()).poll_next(cx)
+    }
+}
+
+impl<T: for<'a> Deserialize<'a> + Clone + Send + Sync + 'static, R> Drop for Subscription<T, R> {
+    fn drop(&mut self) {
+        if let Some(Err(e)) = self.closer.take().map(|closer| closer.unsubscribe(self.cert_key)) {
+            ::ftlog::debug!("drop subscription: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+/*
+测试公用的辅助函数
+*/
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::io::Write;
+use serde::de::DeserializeOwned;
+use crate::common::model::*;
+use crate::common::timer::get_coarse_timestamp_ms;
+use crate::reader::subscribe_reader::*;
+
+/// 临时文件路径(已删除旧文件)
+#[allow(dead_code)]
+pub fn temp_path(name: &str, ext: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("pb_file_reader_{}_{}.{}", name, std::process::id(), ext));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// 清空并创建临时目录
+#[allow(dead_code)]
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pb_file_reader_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 通知读取一次
+#[allow(dead_code)]
+pub fn notify<T: DeserializeOwned + Clone + Send + Sync + 'static, F: FileType>(reader: &SubsReader<T, F>) {
+    reader.notify_meta.sender.send(NotifyEventData {
+        event: NotifyEvent::ScheduleEvent,
+        last_notify_time: get_coarse_timestamp_ms(),
+    }).unwrap();
+}
+
+/// 追加内容后通知读取
+#[allow(dead_code)]
+pub fn append<T: DeserializeOwned + Clone + Send + Sync + 'static>(reader: &CsvReader<T>, path: &Path, content: &str) {
+    std::fs::OpenOptions::new().append(true).open(path).unwrap().write_all(content.as_bytes()).unwrap();
+    notify(reader);
+}
+
+/// 等待条件满足, 最多5秒
+#[allow(dead_code)]
+pub fn wait_until<F: Fn() -> bool>(cond: F) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !cond() {
+        if Instant::now() > deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    true
+}
@@ -4,7 +4,32 @@
 测试程序
 */
 
+mod common;
 mod test_notify;
 mod test_csv_reader;
 mod test_dbf_reader;
+mod test_dbf_writer;
+mod test_csv_writer;
+mod test_correlator;
+mod test_checkpoint;
+mod test_durable_subscribe;
+mod test_replay;
+mod test_envelope;
+mod test_read_error;
+mod test_dead_letter;
+mod test_stream;
+mod test_snapshot;
+mod test_retention;
+mod test_filter;
+mod test_key_routing;
+mod test_backpressure;
+mod test_shared_rows;
+mod test_auto_unsubscribe;
+mod test_subscription;
+mod test_update_filter;
+mod test_consumer_group;
+mod test_pause;
+mod test_batching;
+#[cfg(feature = "async")]
+mod test_async_stream;
 mod bench_csv_reader;
\ No newline at end of file
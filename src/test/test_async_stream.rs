@@ -0,0 +1,98 @@
+#[allow(unused_imports)]
+mod test {
+    use std::future::Future;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::time::{Duration, Instant};
+
+    use futures_core::Stream;
+    use crate::reader::stream::StreamItem;
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    // 被唤醒时unpark执行线程
+    struct ThreadWaker(std::thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    // 最简执行器: Pending时park等待唤醒, 超时panic; 返回结果及poll次数
+    #[allow(dead_code)]
+    fn block_on<F: Future>(fut: F, timeout: Duration) -> (F::Output, usize) {
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = std::pin::pin!(fut);
+        let deadline = Instant::now() + timeout;
+        let mut polls = 0;
+        loop {
+            polls += 1;
+            if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+                return (output, polls);
+            }
+            assert!(Instant::now() < deadline, "future not woken in {:?}", timeout);
+            std::thread::park_timeout(deadline.saturating_duration_since(Instant::now()));
+        }
+    }
+
+    #[allow(dead_code)]
+    fn data_ids(item: Option<StreamItem<TestTradeStruct>>) -> Vec<String> {
+        match item {
+            Some(StreamItem::Data(rows)) => rows.into_iter().map(|row| row.unwrap().trade_id).collect(),
+            other => panic!("expect data, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_csv_async_subscribe() {
+        let dir = temp_dir("async_stream");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ID,TRADE_TIME\nT1,1\nT2,2\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+
+        let mut recv = reader.subscribe_async("", |_, _| true);
+        assert_eq!(data_ids(block_on(recv.recv(), Duration::from_secs(5)).0), vec!["T1", "T2"]);
+        assert!(matches!(block_on(recv.recv(), Duration::from_secs(5)).0, Some(StreamItem::CaughtUp { .. })));
+
+        // 等待中的任务在新数据到达时被唤醒(没有忙轮询)
+        let writer = {
+            let (reader, path) = (reader.clone(), path.clone());
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(200));
+                std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"T3,3\n").unwrap();
+                reader.notify_meta.sender.send(NotifyEventData {
+                    event: NotifyEvent::ScheduleEvent,
+                    last_notify_time: get_coarse_timestamp_ms(),
+                }).unwrap();
+            })
+        };
+        let (item, polls) = block_on(std::future::poll_fn(|cx| Pin::new(&mut recv).poll_next(cx)), Duration::from_secs(5));
+        assert_eq!(data_ids(item), vec!["T3"]);
+        assert!(polls <= 3, "polled {} times", polls);
+        writer.join().unwrap();
+
+        // 取消订阅后Stream结束
+        let _ = reader.unsubscribe(recv.cert_key());
+        block_on(async {
+            while recv.recv().await.is_some() {}
+        }, Duration::from_secs(5));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
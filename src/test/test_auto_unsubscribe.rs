@@ -0,0 +1,65 @@
+#[allow(unused_imports)]
+mod test {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::sync::atomic::Ordering;
+    use std::time::{Duration, Instant};
+
+    use crate::reader::error::ReadResult;
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    #[allow(dead_code)]
+    fn ids(data: Vec<ReadResult<TestTradeStruct>>) -> Vec<String> {
+        data.into_iter().map(|row| row.unwrap().trade_id).collect()
+    }
+
+    #[test]
+    fn test_csv_auto_unsubscribe() {
+        let dir = temp_dir("auto_unsubscribe");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ID,TRADE_TIME\nT1,1\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+
+        let (cert_key1, recv_chan1) = reader.subscribe_filter(Box::new(|_| true)).detach();
+        assert_eq!(ids(recv_chan1.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T1"]);
+        let (cert_key2, recv_chan2) = reader.subscribe_filter(Box::new(|_| true)).detach();
+        assert_eq!(ids(recv_chan2.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T1"]);
+
+        // 接收端关闭后, 下一次发送时移除该订阅, 其他订阅者不受影响
+        drop(recv_chan2);
+        append(&reader, &path, "T2,2\n");
+        assert_eq!(ids(recv_chan1.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T2"]);
+        assert!(wait_until(|| !reader.msg_dispatcher.contains(cert_key2)));
+        assert!(reader.is_running.load(Ordering::Relaxed));
+
+        // 最后一个订阅者关闭后读线程停止
+        drop(recv_chan1);
+        append(&reader, &path, "T3,3\n");
+        assert!(wait_until(|| !reader.is_running.load(Ordering::Relaxed)));
+        assert!(!reader.msg_dispatcher.contains(cert_key1));
+        assert!(reader.empty().unwrap());
+
+        // 之后的订阅重新启动读线程
+        let (cert_key3, recv_chan3) = reader.subscribe_filter(Box::new(|_| true)).detach();
+        assert_eq!(ids(recv_chan3.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T1", "T2", "T3"]);
+        append(&reader, &path, "T4,4\n");
+        assert_eq!(ids(recv_chan3.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T4"]);
+
+        let _ = reader.unsubscribe(cert_key3);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
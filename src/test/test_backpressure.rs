@@ -0,0 +1,191 @@
+#[allow(unused_imports)]
+mod test {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crossbeam::channel::Receiver;
+    use crate::reader::backpressure::{self, OverflowPolicy, OverflowStats, SubscribeOptions};
+    use crate::reader::batching::BatchLimits;
+    use crate::reader::error::ReadResult;
+    use crate::reader::msg_dispatcher::MsgDispatcher;
+    use crate::reader::stream::StreamItem;
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    #[allow(dead_code)]
+    fn batch(ids: &[&str]) -> Vec<ReadResult<TestTradeStruct>> {
+        ids.iter().map(|id| Ok(TestTradeStruct { trade_id: id.to_string(), trade_time: 0 })).collect()
+    }
+
+    #[allow(dead_code)]
+    fn ids(data: Vec<ReadResult<TestTradeStruct>>) -> Vec<String> {
+        data.into_iter().map(|row| row.unwrap().trade_id).collect()
+    }
+
+    #[allow(dead_code)]
+    fn drain(recv_chan: &Receiver<Vec<ReadResult<TestTradeStruct>>>) -> Vec<Vec<String>> {
+        recv_chan.try_iter().map(ids).collect()
+    }
+
+    // 容量为2的订阅者不消费, 连续分发三个批次
+    #[allow(dead_code)]
+    fn overflow_with(policy: OverflowPolicy) -> (Vec<Vec<String>>, OverflowStats) {
+        let dispatcher = MsgDispatcher::<TestTradeStruct>::new();
+        let (send_chan, recv_chan) = backpressure::channel(&SubscribeOptions::default().with_capacity(2).with_overflow(policy));
+        let cert_key = dispatcher.get_cert();
+        dispatcher.subscribe_filter(Box::new(|_| true), send_chan, cert_key);
+        for id in ["T1", "T2", "T3"] {
+            dispatcher.dispatch(batch(&[id])).unwrap();
+        }
+        let stats = dispatcher.overflow_stats(cert_key).unwrap();
+        (drain(&recv_chan), stats)
+    }
+
+    #[test]
+    fn test_overflow_policies() {
+        let (received, stats) = overflow_with(OverflowPolicy::DropNewest);
+        assert_eq!(received, vec![vec!["T1"], vec!["T2"]]);
+        assert_eq!(stats, OverflowStats { dropped_batches: 1, dropped_rows: 1, coalesced_batches: 0 });
+
+        let (received, stats) = overflow_with(OverflowPolicy::DropOldest);
+        assert_eq!(received, vec![vec!["T2"], vec!["T3"]]);
+        assert_eq!(stats, OverflowStats { dropped_batches: 1, dropped_rows: 1, coalesced_batches: 0 });
+
+        let (received, stats) = overflow_with(OverflowPolicy::Coalesce);
+        assert_eq!(received, vec![vec!["T1", "T2", "T3"]]);
+        assert_eq!(stats, OverflowStats { dropped_batches: 0, dropped_rows: 0, coalesced_batches: 2 });
+    }
+
+    #[test]
+    fn test_coalesce_within_batch_limits() {
+        let dispatcher = MsgDispatcher::<TestTradeStruct>::new().with_batch_limits(BatchLimits { max_rows: Some(2), ..Default::default() });
+        let (send_chan, recv_chan) = backpressure::channel(&SubscribeOptions::default().with_capacity(2).with_overflow(OverflowPolicy::Coalesce));
+        let cert_key = dispatcher.get_cert();
+        dispatcher.subscribe_filter(Box::new(|_| true), send_chan, cert_key);
+        for id in ["T1", "T2", "T3", "T4"] {
+            dispatcher.dispatch(batch(&[id])).unwrap();
+        }
+        assert_eq!(drain(&recv_chan), vec![vec!["T1", "T2"], vec!["T3", "T4"]]);
+
+        for id in ["T5", "T6", "T7", "T8", "T9"] { // 达到行数上限无法合并且超出容量时丢弃最早的批次
+            dispatcher.dispatch(batch(&[id])).unwrap();
+        }
+        assert_eq!(drain(&recv_chan), vec![vec!["T7", "T8"], vec!["T9"]]);
+        let stats = dispatcher.overflow_stats(cert_key).unwrap();
+        assert_eq!((stats.dropped_batches, stats.dropped_rows), (1, 2));
+    }
+
+    #[test]
+    fn test_overflow_keep_control_events() {
+        let label = |item: StreamItem<TestTradeStruct>| match item {
+            StreamItem::Data(data) => ids(data).join(","),
+            StreamItem::SchemaChanged { .. } => "schema".to_string(),
+            StreamItem::FileReset { .. } => "reset".to_string(),
+            other => format!("{:?}", other),
+        };
+        // 控制事件不可合并且不丢弃, 超出容量时只丢弃数据批次
+        for policy in [OverflowPolicy::DropOldest, OverflowPolicy::Coalesce] {
+            let (send_chan, recv_chan) = backpressure::channel::<StreamItem<TestTradeStruct>>(&SubscribeOptions::default().with_capacity(2).with_overflow(policy));
+            let send_chan = send_chan.with_batch_limits(&BatchLimits { max_rows: Some(1), ..Default::default() });
+            send_chan.send(StreamItem::Data(batch(&["T1"])));
+            send_chan.send(StreamItem::SchemaChanged { fields: vec![] });
+            send_chan.send(StreamItem::Data(batch(&["T2"])));
+            send_chan.send(StreamItem::Data(batch(&["T3"])));
+            assert_eq!(recv_chan.try_iter().map(label).collect::<Vec<_>>(), vec!["schema", "T3"], "{:?}", policy);
+
+            send_chan.send(StreamItem::SchemaChanged { fields: vec![] });
+            send_chan.send(StreamItem::Data(batch(&["T4"])));
+            send_chan.send(StreamItem::FileReset { identity: Default::default() });
+            assert_eq!(recv_chan.try_iter().map(label).collect::<Vec<_>>(), vec!["schema", "reset"], "{:?}", policy);
+            assert_eq!(send_chan.stats().dropped_rows, 3, "{:?}", policy);
+        }
+    }
+
+    #[test]
+    fn test_drop_receiver_unsubscribe() {
+        for policy in [OverflowPolicy::DropOldest, OverflowPolicy::Coalesce] {
+            let dispatcher = MsgDispatcher::<TestTradeStruct>::new();
+            let (send_chan, recv_chan) = backpressure::channel(&SubscribeOptions::default().with_capacity(2).with_overflow(policy));
+            let cert_key = dispatcher.get_cert();
+            dispatcher.subscribe_filter(Box::new(|_| true), send_chan, cert_key);
+            dispatcher.dispatch(batch(&["T1"])).unwrap();
+            let cloned = recv_chan.clone();
+            drop(recv_chan);
+            dispatcher.dispatch(batch(&["T2"])).unwrap();
+            assert!(dispatcher.contains(cert_key)); // 克隆的接收端仍在
+            assert_eq!(drain(&cloned), vec![vec!["T1"], vec!["T2"]]);
+
+            drop(cloned);
+            dispatcher.dispatch(batch(&["T3"])).unwrap();
+            assert!(!dispatcher.contains(cert_key), "{:?}", policy);
+        }
+    }
+
+    #[test]
+    fn test_overflow_disconnect() {
+        let dispatcher = MsgDispatcher::<TestTradeStruct>::new();
+        let (send_chan, recv_chan) = backpressure::channel(&SubscribeOptions::default().with_capacity(1).with_overflow(OverflowPolicy::Disconnect));
+        let cert_key = dispatcher.get_cert();
+        dispatcher.subscribe_stream(Box::new(|_| true), send_chan, cert_key);
+        let (block_send, block_recv) = crossbeam::channel::bounded(16); // 原有方式传入的通道按Block处理
+        let block_cert = dispatcher.get_cert();
+        dispatcher.subscribe_filter(Box::new(|_| true), block_send, block_cert);
+
+        dispatcher.dispatch(batch(&["T1"])).unwrap();
+        dispatcher.dispatch(batch(&["T2"])).unwrap();
+        assert!(!dispatcher.contains(cert_key)); // 已移除, 其他订阅者不受影响
+        assert!(dispatcher.contains(block_cert));
+        assert_eq!(drain(&block_recv), vec![vec!["T1"], vec!["T2"]]);
+
+        assert!(matches!(recv_chan.recv().unwrap(), StreamItem::Data(rows) if ids(rows.clone()) == vec!["T1"]));
+        assert!(matches!(recv_chan.recv().unwrap(), StreamItem::Disconnected));
+        assert!(recv_chan.recv().is_err());
+    }
+
+    #[test]
+    fn test_csv_slow_subscriber_not_block() {
+        let dir = temp_dir("backpressure");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ID,TRADE_TIME\nT1,1\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+
+        let options = SubscribeOptions::default().with_capacity(1).with_overflow(OverflowPolicy::DropNewest);
+        let (slow_cert, slow_chan) = reader.subscribe_with(Box::new(|_| true), options).detach(); // 不消费
+        let (fast_cert, fast_chan) = reader.subscribe_filter(Box::new(|_| true)).detach();
+        assert_eq!(ids(fast_chan.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T1"]);
+
+        for i in 2..6 {
+            std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(format!("T{},{}\n", i, i).as_bytes()).unwrap();
+            reader.notify_meta.sender.send(NotifyEventData {
+                event: NotifyEvent::ScheduleEvent,
+                last_notify_time: get_coarse_timestamp_ms(),
+            }).unwrap();
+            assert_eq!(ids(fast_chan.recv_timeout(Duration::from_secs(5)).unwrap()), vec![format!("T{}", i)]);
+        }
+        // 同一批次发给两个订阅者的先后不确定, 等待最后一次分发完成
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while reader.overflow_stats(slow_cert).unwrap().dropped_batches < 4 && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(reader.overflow_stats(slow_cert).unwrap(), OverflowStats { dropped_batches: 4, dropped_rows: 4, coalesced_batches: 0 });
+        assert_eq!(drain(&slow_chan), vec![vec!["T1"]]);
+
+        let _ = reader.unsubscribe(fast_cert);
+        let _ = reader.unsubscribe(slow_cert);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,97 @@
+#[allow(unused_imports)]
+mod test {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crossbeam::channel::Receiver;
+    use crate::reader::batching::BatchLimits;
+    use crate::reader::error::ReadResult;
+    use crate::reader::options::ReaderOptions;
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    // 接收指定行数, 返回每个批次的成交编号
+    #[allow(dead_code)]
+    fn recv_batches(recv_chan: &Receiver<Vec<ReadResult<TestTradeStruct>>>, count: usize) -> Vec<Vec<String>> {
+        let mut batches: Vec<Vec<String>> = vec![];
+        while batches.iter().map(Vec::len).sum::<usize>() < count {
+            let batch = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+            batches.push(batch.into_iter().map(|row| row.unwrap().trade_id).collect());
+        }
+        batches
+    }
+
+    // 写入T1..Tn, 每行5字节
+    #[allow(dead_code)]
+    fn create_reader(name: &str, rows: usize, options: ReaderOptions) -> (PathBuf, CsvReader<TestTradeStruct>) {
+        let dir = temp_dir(name);
+        let path = dir.join("trade.csv");
+        let content = (1..=rows).map(|i| format!("T{},{}\n", i, i)).collect::<String>();
+        std::fs::write(&path, format!("TRADE_ID,TRADE_TIME\n{}", content)).unwrap();
+        let reader = CsvReader::<TestTradeStruct>::with_options(path.clone(), true, EncType::UTF8, options).unwrap();
+        (dir, reader)
+    }
+
+    #[test]
+    fn test_batch_limits_split() {
+        let sizes = [5, 5, 20, 5, 5];
+        assert_eq!(BatchLimits::default().split(5, |i| sizes[i]), vec![0..5]);
+        let by_rows = BatchLimits { max_rows: Some(2), ..Default::default() };
+        assert_eq!(by_rows.split(5, |i| sizes[i]), vec![0..2, 2..4, 4..5]);
+        let by_bytes = BatchLimits { max_bytes: Some(12), ..Default::default() };
+        assert_eq!(by_bytes.split(5, |i| sizes[i]), vec![0..2, 2..3, 3..5]); // 单行超出时单独成批
+        let both = BatchLimits { max_rows: Some(1), max_bytes: Some(100), linger: None };
+        assert_eq!(both.split(3, |i| sizes[i]), vec![0..1, 1..2, 2..3]);
+        assert_eq!(by_rows.split(0, |i| sizes[i]), vec![0..0]);
+    }
+
+    #[test]
+    fn test_csv_batch_limits() {
+        // 按行数拆分, 顺序不变
+        let (dir, reader) = create_reader("batch_rows", 10, ReaderOptions::default().with_batch_rows(3));
+        let (cert_key, recv_chan) = reader.subscribe_filter(Box::new(|_| true)).detach();
+        let batches = recv_batches(&recv_chan, 10);
+        assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), vec![3, 3, 3, 1]);
+        assert_eq!(batches.concat(), (1..=10).map(|i| format!("T{}", i)).collect::<Vec<_>>());
+        let _ = reader.unsubscribe(cert_key);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // 按字节数拆分
+        let (dir, reader) = create_reader("batch_bytes", 5, ReaderOptions::default().with_batch_bytes(12));
+        let (cert_key, recv_chan) = reader.subscribe_filter(Box::new(|_| true)).detach();
+        assert_eq!(recv_batches(&recv_chan, 5).iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 2, 1]);
+        let _ = reader.unsubscribe(cert_key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_csv_linger() {
+        let (dir, reader) = create_reader("linger", 1, ReaderOptions::default().with_linger(Duration::from_millis(200)));
+        let path = dir.join("trade.csv");
+        let (cert_key, recv_chan) = reader.subscribe_filter(Box::new(|_| true)).detach();
+        assert_eq!(recv_batches(&recv_chan, 1), vec![vec!["T1"]]);
+
+        // linger期间的多次写入合并为一次读取
+        append(&reader, &path, "T2,2\n");
+        append(&reader, &path, "T3,3\n");
+        append(&reader, &path, "T4,4\n");
+        assert_eq!(recv_batches(&recv_chan, 3), vec![vec!["T2", "T3", "T4"]]);
+
+        let _ = reader.unsubscribe(cert_key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
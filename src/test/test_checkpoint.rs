@@ -0,0 +1,143 @@
+#[allow(unused_imports)]
+mod test {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::reader::checkpoint::*;
+    use crate::reader::options::ReaderOptions;
+    use crate::reader::subscribe_reader::*;
+    use crate::writer::csv_writer::CsvWriter;
+    use crate::common::model::*;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestOrderStruct {
+        order_id: String,
+        qty: i32,
+    }
+
+    #[allow(dead_code)]
+    fn order(i: i32) -> TestOrderStruct {
+        TestOrderStruct { order_id: format!("ORD{}", i), qty: i * 100 }
+    }
+
+    #[allow(dead_code)]
+    fn recv_orders(reader: &CsvReader<TestOrderStruct>) -> Vec<TestOrderStruct> {
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        let _ = reader.unsubscribe(cert_key);
+        data.into_iter().map(|d| d.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_file_checkpoint_store() {
+        let dir = temp_dir("ckpt_store");
+        let store = FileCheckpointStore::new(dir.join("ckpt")).unwrap();
+        let key = "CSV|/some/dir/委托 文件.csv";
+        assert_eq!(store.load(key).unwrap(), None);
+
+        let checkpoint = Checkpoint {
+            identity: FileIdentity { dev: 1, ino: 2, created_ms: 3 },
+            seek_pos: 1024,
+            update_time: 4,
+        };
+        store.save(key, &checkpoint).unwrap();
+        assert_eq!(store.load(key).unwrap(), Some(checkpoint));
+        assert_eq!(store.load("CSV|/other.csv").unwrap(), None);
+
+        let checkpoint = Checkpoint { seek_pos: 2048, ..checkpoint };
+        store.save(key, &checkpoint).unwrap();
+        assert_eq!(store.load(key).unwrap(), Some(checkpoint));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_reader_resume_from_checkpoint() {
+        let dir = temp_dir("ckpt_resume");
+        let path = dir.join("order.csv");
+        let writer = CsvWriter::<TestOrderStruct>::new(path.clone(), EncType::UTF8);
+        writer.append_all(&[order(1), order(2)]).unwrap();
+        let options = ReaderOptions::default().with_file_checkpoint(dir.join("ckpt")).unwrap();
+
+        let reader = Arc::new(CsvReader::<TestOrderStruct>::with_options(path.clone(), true, EncType::UTF8, options.clone()).unwrap());
+        assert_eq!(recv_orders(&reader), vec![order(1), order(2)]);
+        // 分发之后才保存检查点
+        let file_len = std::fs::metadata(&path).unwrap().len();
+        let checkpoint = reader.checkpoint.clone().unwrap();
+        for _ in 0..100 {
+            if checkpoint.load_seek_pos() == file_len {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(checkpoint.load_seek_pos(), file_len);
+        drop(reader);
+
+        // 重启后从上次位置继续
+        writer.append(&order(3)).unwrap();
+        let reader = Arc::new(CsvReader::<TestOrderStruct>::with_options(path.clone(), true, EncType::UTF8, options.clone()).unwrap());
+        assert_eq!(recv_orders(&reader), vec![order(3)]);
+        drop(reader);
+
+        // 文件重建后身份变化, 从头读
+        std::fs::remove_file(&path).unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        writer.append_all(&[order(4), order(5)]).unwrap();
+        let reader = Arc::new(CsvReader::<TestOrderStruct>::with_options(path.clone(), true, EncType::UTF8, options).unwrap());
+        assert_eq!(reader.seek_pos.load(std::sync::atomic::Ordering::Relaxed), 0);
+        assert_eq!(recv_orders(&reader), vec![order(4), order(5)]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_throttled_checkpoint() {
+        let dir = temp_dir("ckpt_throttle");
+        let path = dir.join("order.csv");
+        std::fs::File::create(&path).unwrap();
+        let store: Arc<dyn CheckpointStore> = Arc::new(FileCheckpointStore::new(dir.join("ckpt")).unwrap());
+        let reader_checkpoint = ReaderCheckpoint { store: store.clone(), key: "order".to_string(), file_path: path.clone() };
+        let saved = || store.load("order").unwrap().map(|c| c.seek_pos);
+
+        // 首次立即保存, 间隔内的位置延后保存
+        let mut checkpoint = ThrottledCheckpoint::new(Some(reader_checkpoint));
+        checkpoint.save(10);
+        checkpoint.save(20);
+        assert_eq!(saved(), Some(10));
+        // 重置位置立即保存
+        checkpoint.save_now(0);
+        assert_eq!(saved(), Some(0));
+        checkpoint.save(30);
+        assert_eq!(saved(), Some(0));
+        // 读线程退出时写入未保存的位置
+        drop(checkpoint);
+        assert_eq!(saved(), Some(30));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_checkpoint_flush_when_idle() {
+        let dir = temp_dir("ckpt_idle");
+        let path = dir.join("order.csv");
+        let writer = CsvWriter::<TestOrderStruct>::new(path.clone(), EncType::UTF8);
+        writer.append(&order(1)).unwrap();
+        let options = ReaderOptions::default().with_file_checkpoint(dir.join("ckpt")).unwrap();
+        let reader = Arc::new(CsvReader::<TestOrderStruct>::with_options(path.clone(), true, EncType::UTF8, options).unwrap());
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // 一次写入后文件不再变动, 间隔内延后的位置到期后保存
+        for i in 2..5 {
+            writer.append(&order(i)).unwrap();
+            notify(&reader);
+            recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        }
+        std::thread::sleep(CHECKPOINT_SAVE_INTERVAL + Duration::from_millis(500));
+        let file_len = std::fs::metadata(&path).unwrap().len();
+        assert_eq!(reader.checkpoint.clone().unwrap().load_seek_pos(), file_len);
+        let _ = reader.unsubscribe(cert_key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,204 @@
+#[allow(unused_imports)]
+mod test {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use crossbeam::channel::Receiver;
+    use crate::reader::error::ReadResult;
+    use crate::reader::backpressure::SubscribeOptions;
+    use crate::reader::consumer_group::{GroupOptions, PendingOverflow};
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestOrderStruct {
+        #[serde(rename = "ORDER_ID")]
+        order_id: String,
+        #[serde(rename = "QTY")]
+        qty: i32,
+    }
+
+    // 接收指定行数的(订单号, 数量), 可能分多个批次
+    #[allow(dead_code)]
+    fn recv_rows(recv_chan: &Receiver<Vec<ReadResult<TestOrderStruct>>>, count: usize) -> Vec<(String, i32)> {
+        let mut received = vec![];
+        while received.len() < count {
+            let batch = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+            received.extend(batch.into_iter().map(|row| row.map(|data| (data.order_id, data.qty)).unwrap()));
+        }
+        received
+    }
+
+    #[allow(dead_code)]
+    fn rows(expect: &[(&str, i32)]) -> Vec<(String, i32)> {
+        expect.iter().map(|(id, qty)| (id.to_string(), *qty)).collect()
+    }
+
+    #[test]
+    fn test_csv_consumer_group() {
+        let dir = temp_dir("consumer_group");
+        let path = dir.join("order.csv");
+        std::fs::write(&path, "ORDER_ID,QTY\nO1,1\nO2,2\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestOrderStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+
+        assert!(reader.join_group("workers").is_err()); // 组不存在
+        reader.create_group("workers", Box::new(|_| true), |data| data.order_id.as_str()).unwrap();
+        assert!(reader.create_group("workers", Box::new(|_| true), |data| data.order_id.as_str()).is_err());
+
+        let worker1 = reader.join_group("workers").unwrap();
+        assert_eq!(recv_rows(&worker1, 2), rows(&[("O1", 1), ("O2", 2)]));
+        let worker2 = reader.join_group("workers").unwrap();
+
+        // 每行只发给一个成员, 已分配的key固定发给原成员, 新key轮询分配
+        append(&reader, &path, "O1,3\nO3,4\nO4,5\nO2,6\n");
+        assert_eq!(recv_rows(&worker1, 3), rows(&[("O1", 3), ("O3", 4), ("O2", 6)]));
+        assert_eq!(recv_rows(&worker2, 1), rows(&[("O4", 5)]));
+
+        // 成员退出时未取走的数据转给其他成员, 之后该key也发给新成员
+        append(&reader, &path, "O4,7\nO5,8\n");
+        assert_eq!(recv_rows(&worker1, 1), rows(&[("O5", 8)]));
+        assert!(wait_until(|| !worker2.is_empty()));
+        drop(worker2);
+        assert_eq!(recv_rows(&worker1, 1), rows(&[("O4", 7)]));
+        append(&reader, &path, "O4,9\n");
+        assert_eq!(recv_rows(&worker1, 1), rows(&[("O4", 9)]));
+
+        // 最后一个成员退出后暂存, 新成员加入后发送并继续读取
+        append(&reader, &path, "O6,10\n");
+        assert!(wait_until(|| !worker1.is_empty()));
+        drop(worker1);
+        let group = reader.msg_dispatcher.group("workers").unwrap();
+        assert_eq!(group.members(), 0);
+        assert_eq!(group.pending(), 1);
+        let worker3 = reader.join_group("workers").unwrap();
+        assert_eq!(recv_rows(&worker3, 1), rows(&[("O6", 10)]));
+        append(&reader, &path, "O7,11\n");
+        assert_eq!(recv_rows(&worker3, 1), rows(&[("O7", 11)]));
+
+        drop(worker3);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_csv_consumer_group_member_full() {
+        let dir = temp_dir("consumer_group_full");
+        let path = dir.join("order.csv");
+        std::fs::write(&path, "ORDER_ID,QTY\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestOrderStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+        reader.create_group("workers", Box::new(|_| true), |data| data.order_id.as_str()).unwrap();
+        let slow = reader.join_group_with("workers", SubscribeOptions::default().with_capacity(1)).unwrap();
+        let fast = reader.join_group("workers").unwrap();
+        let group = reader.msg_dispatcher.group("workers").unwrap();
+
+        // 成员通道满时不阻塞读线程, 其他成员照常接收; 该成员的行暂存
+        append(&reader, &path, "O1,1\nO2,2\n");
+        assert_eq!(recv_rows(&fast, 1), rows(&[("O2", 2)]));
+        append(&reader, &path, "O1,3\n");
+        assert!(wait_until(|| group.pending() == 1));
+        append(&reader, &path, "O2,4\nO1,5\n");
+        assert_eq!(recv_rows(&fast, 1), rows(&[("O2", 4)]));
+        assert!(wait_until(|| group.pending() == 2));
+
+        // 通道有空位后, 下次分发时暂存的行按原顺序发送
+        assert_eq!(recv_rows(&slow, 1), rows(&[("O1", 1)]));
+        append(&reader, &path, "O2,6\n");
+        assert_eq!(recv_rows(&fast, 1), rows(&[("O2", 6)]));
+        assert_eq!(recv_rows(&slow, 2), rows(&[("O1", 3), ("O1", 5)]));
+        assert_eq!(group.pending(), 0);
+
+        drop(slow);
+        drop(fast);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_csv_consumer_group_detach() {
+        let dir = temp_dir("consumer_group_detach");
+        let path = dir.join("order.csv");
+        std::fs::write(&path, "ORDER_ID,QTY\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestOrderStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+        reader.create_group("workers", Box::new(|_| true), |data| data.order_id.as_str()).unwrap();
+        let (cert_key, detached) = reader.join_group("workers").unwrap().detach();
+        let worker = reader.join_group("workers").unwrap();
+
+        // detach后取消订阅, 通道中未取走的数据同样转给其他成员
+        append(&reader, &path, "O1,1\nO2,2\n");
+        assert_eq!(recv_rows(&worker, 1), rows(&[("O2", 2)]));
+        assert!(wait_until(|| !detached.is_empty()));
+        reader.unsubscribe(cert_key).unwrap();
+        assert_eq!(recv_rows(&worker, 1), rows(&[("O1", 1)]));
+        assert!(detached.is_empty());
+
+        // 解析失败的行轮询分配
+        let other = reader.join_group("workers").unwrap();
+        append(&reader, &path, "O3,x\nO4,x\n");
+        for member in [&worker, &other] {
+            let batch = member.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(batch.len(), 1);
+            assert!(batch[0].is_err());
+        }
+
+        drop(worker);
+        drop(other);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_csv_consumer_group_pending_block() {
+        let dir = temp_dir("consumer_group_block");
+        let path = dir.join("order.csv");
+        std::fs::write(&path, "ORDER_ID,QTY\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestOrderStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+        let options = GroupOptions::default().with_max_pending(1);
+        reader.create_group_with("workers", Box::new(|_| true), |data| data.order_id.as_str(), options).unwrap();
+        let worker = reader.join_group_with("workers", SubscribeOptions::default().with_capacity(1)).unwrap();
+        let group = reader.msg_dispatcher.group("workers").unwrap();
+
+        // 默认暂存超出上限时阻塞读线程, 成员取走后继续, 不丢弃
+        append(&reader, &path, "O1,1\n");
+        assert!(wait_until(|| worker.is_full()));
+        append(&reader, &path, "O1,2\n");
+        assert!(wait_until(|| group.pending() == 1));
+        append(&reader, &path, "O1,3\n");
+        assert!(wait_until(|| group.pending() == 2));
+        assert_eq!(recv_rows(&worker, 3), rows(&[("O1", 1), ("O1", 2), ("O1", 3)]));
+        assert_eq!(group.dropped(), 0);
+
+        drop(worker);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_csv_consumer_group_pending_limit() {
+        let dir = temp_dir("consumer_group_pending");
+        let path = dir.join("order.csv");
+        std::fs::write(&path, "ORDER_ID,QTY\nO1,1\nO2,2\nO3,3\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestOrderStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+        let options = GroupOptions::default().with_max_pending(2).with_overflow(PendingOverflow::DropOldest);
+        reader.create_group_with("workers", Box::new(|_| true), |data| data.order_id.as_str(), options).unwrap();
+
+        // 组内无成员时暂存, 超出上限丢弃最早的行
+        let other = reader.subscribe_filter(Box::new(|_| false)); // 启动读取
+        let group = reader.msg_dispatcher.group("workers").unwrap();
+        assert!(wait_until(|| group.dropped() == 1));
+        assert_eq!(group.pending(), 2);
+        let worker = reader.join_group("workers").unwrap();
+        assert_eq!(recv_rows(&worker, 2), rows(&[("O2", 2), ("O3", 3)]));
+
+        // 只能删除无成员的组
+        assert!(reader.remove_group("workers").is_err());
+        drop(worker);
+        reader.remove_group("workers").unwrap();
+        assert!(reader.remove_group("workers").is_err());
+        assert!(reader.join_group("workers").is_err());
+
+        drop(other);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
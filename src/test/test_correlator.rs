@@ -0,0 +1,82 @@
+#[allow(unused_imports)]
+mod test {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use crate::reader::manager::*;
+    use crate::writer::csv_writer::CsvWriter;
+    use crate::writer::correlator::{Correlator, CorrelateError};
+    use crate::common::model::{EncType, NotifyEvent, NotifyEventData};
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestOrderReq {
+        client_id: String,
+        qty: i32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestOrderRsp {
+        client_id: String,
+        status: String,
+    }
+
+    #[test]
+    fn test_correlate_order_response() {
+        let order_path = std::env::temp_dir().join(format!("pb_file_reader_corr_order_{}.csv", std::process::id()));
+        let rsp_path = std::env::temp_dir().join(format!("pb_file_reader_corr_rsp_{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&order_path);
+        std::fs::File::create(&rsp_path).unwrap(); // 监听前文件需存在
+
+        let reader = get_or_create_csv_reader::<TestOrderRsp>(&rsp_path, true, EncType::UTF8).unwrap();
+        let correlator = Correlator::new(
+            Box::new(CsvWriter::<TestOrderReq>::new(order_path.clone(), EncType::UTF8)),
+            reader.clone(),
+            |req: &TestOrderReq| req.client_id.clone(),
+            |rsp: &TestOrderRsp| rsp.client_id.clone(),
+        );
+
+        let pending = correlator.submit(&TestOrderReq { client_id: "C1".to_string(), qty: 100 }).unwrap();
+        assert!(correlator.submit(&TestOrderReq { client_id: "C1".to_string(), qty: 200 }).is_err());
+        assert_eq!(std::fs::read_to_string(&order_path).unwrap(), "client_id,qty\r\nC1,100\r\n");
+
+        // 模拟PB写应答
+        let rsp_writer = CsvWriter::<TestOrderRsp>::new(rsp_path.clone(), EncType::UTF8);
+        rsp_writer.append_all(&[
+            TestOrderRsp { client_id: "OTHER".to_string(), status: "0".to_string() },
+            TestOrderRsp { client_id: "C1".to_string(), status: "1".to_string() },
+        ]).unwrap();
+        reader.notify_meta.sender.send(NotifyEventData {
+            event: NotifyEvent::ScheduleEvent,
+            last_notify_time: get_coarse_timestamp_ms(),
+        }).unwrap();
+        let rsp = pending.wait(Duration::from_secs(10)).unwrap();
+        assert_eq!(rsp, TestOrderRsp { client_id: "C1".to_string(), status: "1".to_string() });
+
+        // 无应答则超时, 并移出等待列表
+        let pending = correlator.submit(&TestOrderReq { client_id: "C2".to_string(), qty: 100 }).unwrap();
+        assert_eq!(correlator.pending_count(), 1);
+        assert_eq!(pending.wait(Duration::from_millis(200)).unwrap_err(), CorrelateError::Timeout);
+        assert_eq!(correlator.pending_count(), 0);
+
+        // 不再等待的句柄drop后移出等待列表
+        let pending = correlator.submit(&TestOrderReq { client_id: "C3".to_string(), qty: 100 }).unwrap();
+        assert_eq!(correlator.pending_count(), 1);
+        drop(pending);
+        assert_eq!(correlator.pending_count(), 0);
+
+        // 取消后重新提交同一关联键, 旧句柄drop不影响新的请求
+        let old = correlator.submit(&TestOrderReq { client_id: "C4".to_string(), qty: 100 }).unwrap();
+        assert!(correlator.cancel(&"C4".to_string()));
+        let _new = correlator.submit(&TestOrderReq { client_id: "C4".to_string(), qty: 200 }).unwrap();
+        drop(old);
+        assert_eq!(correlator.pending_count(), 1);
+
+        drop(correlator);
+        let _ = std::fs::remove_file(&order_path);
+        let _ = std::fs::remove_file(&rsp_path);
+    }
+}
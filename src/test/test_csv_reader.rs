@@ -32,7 +32,7 @@ mod test {
             Ok(mut reader) => {
                 let (cert_key, recv_chan) = reader.subscribe("0", |v, data| {
                     true
-                });
+                }).detach();
                 let mut cnt = 1;
                 loop {
                     cnt += 1;
@@ -73,7 +73,7 @@ mod test {
             let reader = reader.unwrap(); 
             let (cert_key, recv_chan) = reader.subscribe("0", |verify, data| -> bool {
                 (data.a % 2).to_string() == verify
-            });
+            }).detach();
             println!("get cert key: {}", cert_key);
             while cnt <= 10 {
                 match recv_chan.recv() {
@@ -99,7 +99,7 @@ mod test {
             let reader = reader.unwrap(); 
             let (cert_key, recv_chan) = reader.subscribe("1", |verify, data| -> bool {
                 (data.a % 2).to_string() == verify
-            });
+            }).detach();
             println!("get cert key: {}", cert_key);
             while cnt <= 10 {
                 match recv_chan.recv() {
@@ -124,7 +124,7 @@ mod test {
             let reader = reader.unwrap(); 
             let (cert_key, recv_chan) = reader.subscribe("1", |verify, data| -> bool {
                 true
-            });
+            }).detach();
             println!("get cert key: {}", cert_key);
             while cnt <= 10 {
                 match recv_chan.recv() {
@@ -169,11 +169,11 @@ mod test {
 
         let (cert_key1, recv_chan1) = reader1.subscribe(verify_1, |verify_1, data| -> bool {
             (data.a % 2).to_string() == verify_1.to_string() // 这里根据结构选择过滤, 直接为true, 代表接收所有数据
-        });
+        }).detach();
 
         let (cert_key2, recv_chan2) = reader2.subscribe(verify_2, |verify_2, data| -> bool {
             (data.a % 2).to_string() == verify_2.to_string() // 这里根据结构选择过滤, 直接为true, 代表接收所有数据
-        });
+        }).detach();
         let mut selector = crossbeam::channel::Select::new();
         let chan1_idx = selector.recv(&recv_chan1);
         let chan2_idx = selector.recv(&recv_chan2);
@@ -0,0 +1,73 @@
+#[allow(unused_imports)]
+mod test {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use crate::reader::manager::*;
+    use crate::writer::csv_writer::{CsvWriter, CsvDialect};
+    use crate::common::model::EncType;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestOrderStruct {
+        order_id: String,
+        trade_acc: String,
+        qty: i32,
+    }
+
+    #[allow(dead_code)]
+    fn order(i: i32) -> TestOrderStruct {
+        TestOrderStruct { order_id: format!("ORD{}", i), trade_acc: "账户,114514".to_string(), qty: i * 100 }
+    }
+
+    #[test]
+    fn test_csv_writer_gbk() {
+        let path = temp_path("writer_gbk", "csv");
+        let writer = CsvWriter::<TestOrderStruct>::new(path.clone(), EncType::GBK).with_sync(true);
+        writer.append(&order(1)).unwrap();
+        writer.append_all(&[order(2), order(3)]).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let (data, _, had_errors) = encoding_rs::GBK.decode(&bytes);
+        assert!(!had_errors);
+        assert_eq!(data, "order_id,trade_acc,qty\r\n\
+            ORD1,\"账户,114514\",100\r\n\
+            ORD2,\"账户,114514\",200\r\n\
+            ORD3,\"账户,114514\",300\r\n");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_csv_writer_dialect() {
+        let path = temp_path("writer_dialect", "csv");
+        let dialect = CsvDialect {
+            delimiter: b'|',
+            terminator: csv::Terminator::Any(b'\n'),
+            ..Default::default()
+        };
+        let writer = CsvWriter::<TestOrderStruct>::new(path.clone(), EncType::UTF8).with_dialect(dialect);
+        writer.append(&order(1)).unwrap();
+        // 已存在的文件不再写表头
+        let writer = CsvWriter::<TestOrderStruct>::new(path.clone(), EncType::UTF8).with_dialect(dialect);
+        writer.append(&order(2)).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "order_id|trade_acc|qty\nORD1|账户,114514|100\nORD2|账户,114514|200\n");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_csv_writer_with_reader() {
+        let path = temp_path("writer_reader", "csv");
+        let writer = CsvWriter::<TestOrderStruct>::new(path.clone(), EncType::GBK);
+        writer.append_all(&[order(1), order(2)]).unwrap();
+
+        let reader = get_or_create_csv_reader::<TestOrderStruct>(&path, true, EncType::GBK).unwrap();
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(data.into_iter().map(|d| d.unwrap()).collect::<Vec<_>>(), vec![order(1), order(2)]);
+
+        let _ = remove_csv_reader::<TestOrderStruct>(cert_key, true, &path);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -36,7 +36,7 @@ mod test {
             Ok(mut reader) => {
                 let (cert_key, recv_chan) = reader.subscribe("0", |v, data| {
                     true
-                });
+                }).detach();
                 let mut cnt = 1;
                 loop {
                     cnt += 1;
@@ -76,7 +76,7 @@ mod test {
             let reader = reader.unwrap(); 
             let (cert_key, recv_chan) = reader.subscribe("0", |verify, data| -> bool {
                 (data.a  as i32 % 2).to_string() == verify
-            });
+            }).detach();
             println!("get cert key: {}", cert_key);
             while cnt <= 10 {
                 match recv_chan.recv() {
@@ -102,7 +102,7 @@ mod test {
             let reader = reader.unwrap(); 
             let (cert_key, recv_chan) = reader.subscribe("1", |verify, data| -> bool {
                 (data.a as i32 % 2).to_string() == verify
-            });
+            }).detach();
             println!("get cert key: {}", cert_key);
             while cnt <= 10 {
                 match recv_chan.recv() {
@@ -127,7 +127,7 @@ mod test {
             let reader = reader.unwrap(); 
             let (cert_key, recv_chan) = reader.subscribe("1", |verify, data| -> bool {
                 true
-            });
+            }).detach();
             println!("get cert key: {}", cert_key);
             while cnt <= 10 {
                 match recv_chan.recv() {
@@ -147,4 +147,87 @@ mod test {
         let _ = h3.join();
     }
 
-}
\ No newline at end of file
+    // 整体重写DBF文件(模拟write_dbf.py的写法), 记录a字段为1..=count
+    #[cfg(test)]
+    fn rewrite_dbf_file(path: &PathBuf, count: usize) {
+        use std::convert::TryFrom;
+        let writer = dbase::TableWriterBuilder::new()
+            .add_numeric_field(dbase::FieldName::try_from("A").unwrap(), 10, 0)
+            .add_character_field(dbase::FieldName::try_from("B").unwrap(), 50)
+            .add_numeric_field(dbase::FieldName::try_from("C").unwrap(), 13, 3)
+            .build_with_file_dest(path)
+            .unwrap();
+        let records = (1..=count).map(|i| TestDbfStruct1 {
+            a: i as f64,
+            b: format!("Record_{}", i),
+            c: i as f64 / 10.0,
+        }).collect::<Vec<_>>();
+        writer.write_records(&records).unwrap();
+    }
+
+    #[test]
+    fn test_dbf_rewrite_without_duplicate() {
+        use crate::common::model::{NotifyEvent, NotifyEventData};
+        use crate::common::timer::get_coarse_timestamp_ms;
+        use std::time::Duration;
+
+        let path = std::env::temp_dir().join(format!("pb_file_reader_rewrite_{}.dbf", std::process::id()));
+        rewrite_dbf_file(&path, 3);
+
+        let reader = get_or_create_dbf_reader::<TestDbfStruct1>(&path, true, EncType::UTF8).unwrap();
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(data.len(), 3);
+
+        let notify = || {
+            reader.notify_meta.sender.send(NotifyEventData {
+                event: NotifyEvent::ScheduleEvent,
+                last_notify_time: get_coarse_timestamp_ms(),
+            }).unwrap();
+        };
+
+        // 重写过程中文件被清空, 不应重置位置并重复分发
+        std::fs::File::create(&path).unwrap();
+        notify();
+        assert!(recv_chan.recv_timeout(Duration::from_secs(1)).is_err());
+
+        // 重写完成, 仅分发新增记录
+        rewrite_dbf_file(&path, 5);
+        notify();
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        let a_list = data.into_iter().map(|d| d.unwrap().a as i32).collect::<Vec<_>>();
+        assert_eq!(a_list, vec![4, 5]);
+
+        let _ = remove_dbf_reader::<TestDbfStruct1>(cert_key, true, &path);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_wait_dbf_stable() {
+        use crate::reader::dbf_reader::{wait_dbf_stable, DbfFileState};
+        use crate::common::model::{DBF_STABLE_CHECK_INTERVAL, MAX_READ_RETRY_TIME};
+        use std::time::Instant;
+
+        let path = std::env::temp_dir().join(format!("pb_file_reader_stable_{}.dbf", std::process::id()));
+        rewrite_dbf_file(&path, 3);
+        let state = DbfFileState::load(&path).unwrap();
+
+        // 与上次读取时相同, 不等待
+        let start = Instant::now();
+        assert_eq!(wait_dbf_stable(&path, Some(&state), MAX_READ_RETRY_TIME), Some(state));
+        assert!(start.elapsed() < DBF_STABLE_CHECK_INTERVAL);
+
+        // 首次读取或已变化, 间隔后再次检查
+        let start = Instant::now();
+        assert_eq!(wait_dbf_stable(&path, None, MAX_READ_RETRY_TIME), Some(state));
+        assert!(start.elapsed() >= DBF_STABLE_CHECK_INTERVAL);
+
+        // 文件不一致时每次重试只等待一个间隔
+        std::fs::File::create(&path).unwrap();
+        let start = Instant::now();
+        assert_eq!(wait_dbf_stable(&path, Some(&state), MAX_READ_RETRY_TIME), None);
+        assert!(start.elapsed() < DBF_STABLE_CHECK_INTERVAL * (MAX_READ_RETRY_TIME as u32 + 1));
+        let _ = std::fs::remove_file(&path);
+    }
+
+}
@@ -0,0 +1,150 @@
+#[allow(unused_imports)]
+mod test {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use crate::reader::manager::*;
+    use crate::reader::dbf_reader::DbfFileState;
+    use crate::writer::dbf_writer::{DbfWriter, DbfFieldSpec, DbfFieldKind};
+    use crate::common::model::{EncType, NotifyEvent, NotifyEventData};
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestRspStruct {
+        #[serde(rename = "ORDER_ID")]
+        order_id: String,
+        #[serde(rename = "PRICE")]
+        price: f64,
+        #[serde(rename = "FILLED")]
+        filled: bool,
+    }
+
+    #[allow(dead_code)]
+    fn rsp(i: usize) -> TestRspStruct {
+        TestRspStruct { order_id: format!("ORD{}", i), price: i as f64 + 0.5, filled: i.is_multiple_of(2) }
+    }
+
+    #[test]
+    fn test_parse_field_spec() {
+        let specs = DbfFieldSpec::parse("a N(10,0);b C(50);c N(13,3);d L;").unwrap();
+        assert_eq!(specs, vec![
+            DbfFieldSpec::new("a", DbfFieldKind::Numeric, 10, 0),
+            DbfFieldSpec::new("b", DbfFieldKind::Character, 50, 0),
+            DbfFieldSpec::new("c", DbfFieldKind::Numeric, 13, 3),
+            DbfFieldSpec::new("d", DbfFieldKind::Logical, 1, 0),
+        ]);
+        assert!(DbfFieldSpec::parse("a X(1)").is_err());
+        assert!(DbfFieldSpec::parse("b C").is_err());
+    }
+
+    #[test]
+    fn test_dbf_writer_append() {
+        let path = temp_path("writer_append", "dbf");
+        let writer = DbfWriter::<TestRspStruct>::create_from_spec(path.clone(), "ORDER_ID C(20);PRICE N(13,3);FILLED L").unwrap();
+        assert_eq!(writer.record_count().unwrap(), 0);
+        assert_eq!(writer.append(&rsp(1)).unwrap(), 0);
+        assert_eq!(writer.append_all(&[rsp(2), rsp(3)]).unwrap(), 3);
+
+        // 已存在的文件沿用原字段定义继续追加
+        let writer = DbfWriter::<TestRspStruct>::create_from_record(path.clone(), &rsp(0)).unwrap();
+        assert_eq!(writer.append(&rsp(4)).unwrap(), 3);
+        assert!(DbfFileState::load(&path).unwrap().is_consistent());
+
+        let records = dbase::Reader::from_path(&path).unwrap().iter_records_as::<TestRspStruct>()
+            .collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records, (1..=4).map(rsp).collect::<Vec<_>>());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_dbf_writer_infer_spec() {
+        let specs = DbfFieldSpec::from_record(&rsp(1)).unwrap();
+        assert_eq!(specs, vec![
+            DbfFieldSpec::new("ORDER_ID", DbfFieldKind::Character, 50, 0),
+            DbfFieldSpec::new("PRICE", DbfFieldKind::Numeric, 20, 6),
+            DbfFieldSpec::new("FILLED", DbfFieldKind::Logical, 1, 0),
+        ]);
+    }
+
+    #[test]
+    fn test_dbf_writer_with_reader() {
+        let path = temp_path("writer_reader", "dbf");
+        let writer = DbfWriter::<TestRspStruct>::create_from_record(path.clone(), &rsp(0)).unwrap();
+        writer.append(&rsp(1)).unwrap();
+
+        let reader = get_or_create_dbf_reader::<TestRspStruct>(&path, true, EncType::UTF8).unwrap();
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(data.into_iter().map(|d| d.unwrap()).collect::<Vec<_>>(), vec![rsp(1)]);
+
+        writer.append_all(&[rsp(2), rsp(3)]).unwrap();
+        reader.notify_meta.sender.send(NotifyEventData {
+            event: NotifyEvent::ScheduleEvent,
+            last_notify_time: get_coarse_timestamp_ms(),
+        }).unwrap();
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(data.into_iter().map(|d| d.unwrap()).collect::<Vec<_>>(), vec![rsp(2), rsp(3)]);
+
+        let _ = remove_dbf_reader::<TestRspStruct>(cert_key, true, &path);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_dbf_update_record() {
+        use crate::writer::dbf_writer::{update_dbf_record, FieldValue};
+
+        let path = temp_path("update_record", "dbf");
+        let writer = DbfWriter::<TestRspStruct>::create_from_record(path.clone(), &rsp(0)).unwrap();
+        writer.append_all(&[rsp(1), rsp(2), rsp(3)]).unwrap();
+
+        // 非增量读: 每次文件变化都会全量分发
+        let reader = get_or_create_dbf_reader::<TestRspStruct>(&path, false, EncType::UTF8).unwrap();
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        assert_eq!(recv_chan.recv_timeout(Duration::from_secs(5)).unwrap().len(), 3);
+        let notify = || {
+            reader.notify_meta.sender.send(NotifyEventData {
+                event: NotifyEvent::ScheduleEvent,
+                last_notify_time: get_coarse_timestamp_ms(),
+            }).unwrap();
+        };
+
+        // 自身写回不触发重复分发
+        reader.update_record(1, &[("FILLED", FieldValue::Logical(Some(true)))]).unwrap();
+        update_dbf_record(&path, 2, &[("PRICE", FieldValue::Numeric(Some(9.5)))]).unwrap();
+        notify();
+        assert!(recv_chan.recv_timeout(Duration::from_secs(1)).is_err());
+        assert!(reader.update_record(3, &[("FILLED", FieldValue::Logical(Some(true)))]).is_err());
+        assert!(reader.update_record(0, &[("NOT_EXIST", FieldValue::Logical(Some(true)))]).is_err());
+
+        // 其他写入仍正常分发
+        writer.append(&rsp(4)).unwrap();
+        notify();
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap()
+            .into_iter().map(|d| d.unwrap()).collect::<Vec<_>>();
+        let mut expect = (1..=4).map(rsp).collect::<Vec<_>>();
+        expect[1].filled = true;
+        expect[2].price = 9.5;
+        assert_eq!(data, expect);
+
+        // 外部写入覆盖了自身写回的区间: 内容与写回时不同, 仍正常分发
+        reader.update_record(1, &[("FILLED", FieldValue::Logical(Some(true)))]).unwrap();
+        {
+            use std::io::{Seek, SeekFrom, Write};
+            let state = DbfFileState::load(&path).unwrap();
+            let mut fd = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+            fd.seek(SeekFrom::Start(state.header_len + 2 * state.record_len - 1)).unwrap(); // 第1条记录的FILLED字段
+            fd.write_all(b"F").unwrap();
+        }
+        notify();
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap()
+            .into_iter().map(|d| d.unwrap()).collect::<Vec<_>>();
+        expect[1].filled = false;
+        assert_eq!(data, expect);
+
+        let _ = remove_dbf_reader::<TestRspStruct>(cert_key, false, &path);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,146 @@
+#[allow(unused_imports)]
+mod test {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::reader::options::ReaderOptions;
+    use crate::reader::subscribe_reader::*;
+    use crate::reader::dead_letter::read_quarantine;
+    use crate::reader::error::ReadError;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    #[allow(dead_code)]
+    fn trade(id: &str, trade_time: i32) -> TestTradeStruct {
+        TestTradeStruct { trade_id: id.to_string(), trade_time }
+    }
+
+    #[test]
+    fn test_csv_dead_letter_and_redeliver() {
+        let dir = temp_dir("dead_letter");
+        let path = dir.join("trade.csv");
+        let quarantine = dir.join("quarantine.csv");
+        std::fs::write(&path, "TRADE_ID,TRADE_TIME\nT1,93000\nT2,abc\nT3,93200\n").unwrap();
+        let options = ReaderOptions::default().with_dead_letter(16).with_quarantine_file(quarantine.clone());
+
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::with_options(path.clone(), true, EncType::UTF8, options).unwrap());
+        let dead_letters = reader.dead_letters().unwrap();
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(data.into_iter().map(|d| d.unwrap()).collect::<Vec<_>>(), vec![trade("T1", 93000), trade("T3", 93200)]);
+
+        let err = dead_letters.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert!(matches!(err, ReadError::Deserialize { .. }));
+        assert_eq!(err.line(), Some(3));
+        let mut records = read_quarantine(&quarantine).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!((records[0].kind.as_str(), records[0].field.as_str(), records[0].raw.as_str()), ("Deserialize", "TRADE_TIME", "T2,abc"));
+        assert_eq!(records[0].line, 3);
+
+        // 未修正的记录再次进入死信, 修正后的记录正常分发
+        assert_eq!(reader.redeliver(&records).unwrap(), 0);
+        assert_eq!(dead_letters.recv_timeout(Duration::from_secs(1)).unwrap().line(), Some(3));
+        assert_eq!(read_quarantine(&quarantine).unwrap().len(), 2);
+        records[0].raw = "T2,93100".to_string();
+        assert_eq!(reader.redeliver(&records).unwrap(), 1);
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(data.into_iter().map(|d| d.unwrap()).collect::<Vec<_>>(), vec![trade("T2", 93100)]);
+
+        let _ = reader.unsubscribe(cert_key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestPlainTrade { // 字段名与文件表头不同, 按字段顺序反序列化
+        id: String,
+        time: i32,
+    }
+
+    #[test]
+    fn test_redeliver_without_renamed_fields() {
+        let dir = temp_dir("dead_letter_plain");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ID,TRADE_TIME\nT1,93000\nT2,abc\n").unwrap();
+        let options = ReaderOptions::default().with_quarantine_file(dir.join("quarantine.csv"));
+
+        let reader = Arc::new(CsvReader::<TestPlainTrade>::with_options(path.clone(), true, EncType::UTF8, options).unwrap());
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(data.into_iter().map(|d| d.unwrap()).collect::<Vec<_>>(), vec![TestPlainTrade { id: "T1".to_string(), time: 93000 }]);
+
+        let mut records = read_quarantine(&dir.join("quarantine.csv")).unwrap();
+        assert_eq!(records.len(), 1);
+        records[0].raw = "T2,93100".to_string();
+        assert_eq!(reader.redeliver(&records).unwrap(), 1);
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(data.into_iter().map(|d| d.unwrap()).collect::<Vec<_>>(), vec![TestPlainTrade { id: "T2".to_string(), time: 93100 }]);
+
+        let _ = reader.unsubscribe(cert_key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestDbfTrade {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: f64,
+    }
+
+    #[allow(dead_code)]
+    fn dbf_trade(id: &str, trade_time: f64) -> TestDbfTrade {
+        TestDbfTrade { trade_id: id.to_string(), trade_time }
+    }
+
+    #[test]
+    fn test_dbf_quarantine_and_redeliver() {
+        use std::io::{Seek, SeekFrom};
+        use crate::reader::dbf_reader::DbfFileState;
+        use crate::writer::dbf_writer::DbfWriter;
+
+        let dir = temp_dir("dead_letter_dbf");
+        let path = dir.join("trade.dbf");
+        let quarantine = dir.join("quarantine.csv");
+        let writer = DbfWriter::<TestDbfTrade>::create_from_spec(path.clone(), "TRADE_ID C(20);TRADE_TIME N(10,0)").unwrap();
+        writer.append_all(&[dbf_trade("成交1", 93000.0), dbf_trade("成交2", 93100.0), dbf_trade("成交3", 93200.0)]).unwrap();
+        // 第2条记录的数值字段改为非法内容
+        let state = DbfFileState::load(&path).unwrap();
+        let mut fd = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        fd.seek(SeekFrom::Start(state.header_len + state.record_len + 1 + 20)).unwrap();
+        fd.write_all(b"       abc").unwrap();
+        drop(fd);
+        let options = ReaderOptions::default().with_quarantine_file(quarantine.clone());
+
+        let reader = Arc::new(DbfReader::<TestDbfTrade>::with_options(path.clone(), true, EncType::UTF8, options).unwrap());
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(data.into_iter().map(|d| d.unwrap()).collect::<Vec<_>>(), vec![dbf_trade("成交1", 93000.0), dbf_trade("成交3", 93200.0)]);
+
+        // 原始内容按文件编码解码
+        let mut records = read_quarantine(&quarantine).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!((records[0].line, records[0].raw.as_str()), (2, "成交2,abc"));
+        records[0].raw = "成交2,93100".to_string();
+        assert_eq!(reader.redeliver(&records).unwrap(), 1);
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(data.into_iter().map(|d| d.unwrap()).collect::<Vec<_>>(), vec![dbf_trade("成交2", 93100.0)]);
+
+        let _ = reader.unsubscribe(cert_key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,143 @@
+#[allow(unused_imports)]
+mod test {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use crossbeam::channel::Receiver;
+
+    use crate::reader::options::ReaderOptions;
+    use crate::reader::subscribe_reader::*;
+    use crate::writer::csv_writer::CsvWriter;
+    use crate::writer::dbf_writer::DbfWriter;
+    use crate::reader::error::ReadResult;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestOrderStruct {
+        #[serde(rename = "ORDER_ID")]
+        order_id: String,
+        #[serde(rename = "QTY")]
+        qty: i32,
+    }
+
+    #[allow(dead_code)]
+    fn order(i: i32) -> TestOrderStruct {
+        TestOrderStruct { order_id: format!("ORD{}", i), qty: i * 100 }
+    }
+
+    #[allow(dead_code)]
+    fn recv_orders(recv_chan: &Receiver<Vec<ReadResult<TestOrderStruct>>>) -> Vec<TestOrderStruct> {
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        data.into_iter().map(|d| d.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_csv_durable_subscribe_ack() {
+        let dir = temp_dir("durable_csv");
+        let path = dir.join("order.csv");
+        let writer = CsvWriter::<TestOrderStruct>::new(path.clone(), EncType::UTF8);
+        writer.append_all(&[order(1), order(2)]).unwrap();
+        let options = ReaderOptions::default().with_file_checkpoint(dir.join("ckpt")).unwrap();
+
+        let reader = Arc::new(CsvReader::<TestOrderStruct>::with_options(path.clone(), true, EncType::UTF8, options.clone()).unwrap());
+        let (fast_key, fast_chan) = reader.subscribe_durable("fast", "", |_, _| true).unwrap().detach();
+        let (slow_key, slow_chan) = reader.subscribe_durable("slow", "", |_, _| true).unwrap().detach();
+        assert!(reader.subscribe_durable("fast", "", |_, _| true).is_err());
+        assert_eq!(recv_orders(&fast_chan), vec![order(1), order(2)]);
+        assert_eq!(recv_orders(&slow_chan), vec![order(1), order(2)]);
+        reader.ack(fast_key).unwrap();
+
+        writer.append(&order(3)).unwrap();
+        notify(&reader);
+        assert_eq!(recv_orders(&fast_chan), vec![order(3)]);
+        assert_eq!(recv_orders(&slow_chan), vec![order(3)]);
+        let fast_pos = reader.ack(fast_key).unwrap();
+        assert_eq!(fast_pos, std::fs::metadata(&path).unwrap().len());
+        // slow未ack, 重启后需要重新推送
+        let _ = reader.unsubscribe(fast_key);
+        let _ = reader.unsubscribe(slow_key);
+        drop(reader);
+
+        writer.append(&order(4)).unwrap();
+        let reader = Arc::new(CsvReader::<TestOrderStruct>::with_options(path.clone(), true, EncType::UTF8, options).unwrap());
+        let (fast_key, fast_chan) = reader.subscribe_durable("fast", "", |_, _| true).unwrap().detach();
+        let (slow_key, slow_chan) = reader.subscribe_durable("slow", "", |_, _| true).unwrap().detach();
+        assert_eq!(recv_orders(&fast_chan), vec![order(4)]);
+        assert_eq!(recv_orders(&slow_chan), vec![order(1), order(2), order(3), order(4)]);
+        assert_eq!(reader.ack(slow_key).unwrap(), reader.ack(fast_key).unwrap());
+        let _ = reader.unsubscribe(fast_key);
+        let _ = reader.unsubscribe(slow_key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_csv_durable_subscribe_chunked() {
+        let dir = temp_dir("durable_csv_chunked");
+        let path = dir.join("order.csv");
+        let writer = CsvWriter::<TestOrderStruct>::new(path.clone(), EncType::UTF8);
+        writer.append_all(&[order(1), order(2), order(3)]).unwrap();
+        let options = ReaderOptions::default().with_file_checkpoint(dir.join("ckpt")).unwrap().with_batch_rows(1);
+
+        // 补发与实时数据都按批次上限拆分, 每个批次单独ack
+        let reader = Arc::new(CsvReader::<TestOrderStruct>::with_options(path.clone(), true, EncType::UTF8, options.clone()).unwrap());
+        let (cert_key, recv_chan) = reader.subscribe_durable("chunked", "", |_, _| true).unwrap().detach();
+        for i in 1..=3 {
+            assert_eq!(recv_orders(&recv_chan), vec![order(i)]);
+        }
+        writer.append_all(&[order(4), order(5)]).unwrap();
+        notify(&reader);
+        for i in 4..=5 {
+            assert_eq!(recv_orders(&recv_chan), vec![order(i)]);
+        }
+        reader.ack(cert_key).unwrap();
+        let _ = reader.unsubscribe(cert_key);
+        drop(reader);
+
+        // 只ack了第一个批次, 重启后从第二行开始重新推送
+        let reader = Arc::new(CsvReader::<TestOrderStruct>::with_options(path.clone(), true, EncType::UTF8, options).unwrap());
+        let (cert_key, recv_chan) = reader.subscribe_durable("chunked", "", |_, _| true).unwrap().detach();
+        for i in 2..=5 {
+            assert_eq!(recv_orders(&recv_chan), vec![order(i)]);
+        }
+        for _ in 2..=5 {
+            reader.ack(cert_key).unwrap();
+        }
+        assert_eq!(reader.durable.acked_pos(cert_key), Some(std::fs::metadata(&path).unwrap().len()));
+        let _ = reader.unsubscribe(cert_key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_dbf_durable_subscribe_filter() {
+        let dir = temp_dir("durable_dbf");
+        let path = dir.join("order.dbf");
+        let writer = DbfWriter::<TestOrderStruct>::create_from_spec(path.clone(), "ORDER_ID C(20);QTY I").unwrap();
+        writer.append_all(&[order(1), order(2)]).unwrap();
+        let options = ReaderOptions::default().with_file_checkpoint(dir.join("ckpt")).unwrap();
+
+        let reader = Arc::new(DbfReader::<TestOrderStruct>::with_options(path.clone(), true, EncType::UTF8, options.clone()).unwrap());
+        let (cert_key, recv_chan) = reader.subscribe_durable("booking", "ORD1", |verify, data| data.order_id == verify).unwrap().detach();
+        assert_eq!(recv_orders(&recv_chan), vec![order(1)]);
+        assert_eq!(reader.ack(cert_key).unwrap(), 2);
+
+        // 没有匹配数据时消费位置直接推进
+        writer.append(&order(3)).unwrap();
+        notify(&reader);
+        std::thread::sleep(Duration::from_millis(500));
+        assert_eq!(reader.durable.acked_pos(cert_key), Some(3));
+        let _ = reader.unsubscribe(cert_key);
+        drop(reader);
+
+        writer.append(&order(1)).unwrap();
+        let reader = Arc::new(DbfReader::<TestOrderStruct>::with_options(path.clone(), true, EncType::UTF8, options).unwrap());
+        let (cert_key, recv_chan) = reader.subscribe_durable("booking", "ORD1", |verify, data| data.order_id == verify).unwrap().detach();
+        assert_eq!(recv_orders(&recv_chan), vec![order(1)]);
+        assert_eq!(reader.ack(cert_key).unwrap(), 4);
+        let _ = reader.unsubscribe(cert_key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,96 @@
+#[allow(unused_imports)]
+mod test {
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use crossbeam::channel::Receiver;
+
+    use crate::reader::manager::*;
+    use crate::reader::envelope::Envelope;
+    use crate::reader::subscribe_reader::ReplayFrom;
+    use crate::writer::csv_writer::CsvWriter;
+    use crate::writer::dbf_writer::DbfWriter;
+    use crate::reader::error::ReadResult;
+    use crate::common::model::*;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    #[allow(dead_code)]
+    fn trade(i: i32) -> TestTradeStruct {
+        TestTradeStruct { trade_id: format!("成交{}", i), trade_time: 93000 + i * 100 }
+    }
+
+    #[allow(dead_code)]
+    fn recv_envelopes(recv_chan: &Receiver<Vec<ReadResult<Envelope<TestTradeStruct>>>>) -> Vec<Envelope<TestTradeStruct>> {
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        data.into_iter().map(|d| d.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_csv_envelope() {
+        let path = temp_path("envelope", "csv");
+        let writer = CsvWriter::<TestTradeStruct>::new(path.clone(), EncType::GBK);
+        writer.append(&trade(1)).unwrap();
+        let second_offset = std::fs::metadata(&path).unwrap().len();
+        writer.append(&trade(2)).unwrap();
+        let third_offset = std::fs::metadata(&path).unwrap().len();
+
+        let reader = get_or_create_csv_reader::<TestTradeStruct>(&path, true, EncType::GBK).unwrap();
+        let (cert_key, recv_chan) = reader.subscribe_envelope("", |_, _| true).detach();
+        let rows = recv_envelopes(&recv_chan);
+        assert_eq!(rows.iter().map(|r| r.data.clone()).collect::<Vec<_>>(), vec![trade(1), trade(2)]);
+        assert_eq!(rows.iter().map(|r| r.line).collect::<Vec<_>>(), vec![2, 3]); // 表头为第1行
+        assert_eq!(rows[1].offset, second_offset);
+        let content = std::fs::read(&path).unwrap();
+        assert_eq!(content[rows[0].offset as usize - 1], b'\n');
+        assert_eq!(*rows[0].file_path, path);
+        assert_eq!(rows[0].identity, FileIdentity::of(&path).unwrap());
+        assert!(rows[0].seq < rows[1].seq);
+
+        // 通知触发的读取携带通知时间
+        writer.append(&trade(3)).unwrap();
+        reader.notify_meta.sender.send(NotifyEventData {
+            event: NotifyEvent::ScheduleEvent,
+            last_notify_time: 12345,
+        }).unwrap();
+        let rows_after = recv_envelopes(&recv_chan);
+        assert_eq!(rows_after.len(), 1);
+        assert_eq!(rows_after[0].data, trade(3));
+        assert_eq!((rows_after[0].offset, rows_after[0].line), (third_offset, 4));
+        assert_eq!(rows_after[0].notify_time, 12345);
+        assert!(rows_after[0].seq > rows[1].seq);
+
+        // 重放的行序号不变
+        reader.replay(cert_key, ReplayFrom::ByteOffset(second_offset)).unwrap();
+        let replayed = recv_envelopes(&recv_chan);
+        assert_eq!(replayed.iter().map(|r| r.seq).collect::<Vec<_>>(), vec![rows[1].seq, rows_after[0].seq]);
+
+        let _ = remove_csv_reader::<TestTradeStruct>(cert_key, true, &path);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_dbf_envelope() {
+        let path = temp_path("envelope", "dbf");
+        let writer = DbfWriter::<TestTradeStruct>::create_from_spec(path.clone(), "TRADE_ID C(20);TRADE_TIME I").unwrap();
+        writer.append_all(&[trade(1), trade(2), trade(3)]).unwrap();
+
+        let reader = get_or_create_dbf_reader::<TestTradeStruct>(&path, true, EncType::GBK).unwrap();
+        let (cert_key, recv_chan) = reader.subscribe_envelope("", |_, _| true).detach();
+        let rows = recv_envelopes(&recv_chan);
+        assert_eq!(rows.iter().map(|r| r.data.clone()).collect::<Vec<_>>(), vec![trade(1), trade(2), trade(3)]);
+        assert_eq!(rows.iter().map(|r| (r.offset, r.line)).collect::<Vec<_>>(), vec![(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(rows[0].identity, FileIdentity::of(&path).unwrap());
+
+        let _ = remove_dbf_reader::<TestTradeStruct>(cert_key, true, &path);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,68 @@
+#[allow(unused_imports)]
+mod test {
+    use std::collections::HashSet;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::time::Duration;
+
+    use crate::reader::error::ReadResult;
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    #[allow(dead_code)]
+    fn ids(data: Vec<ReadResult<TestTradeStruct>>) -> Vec<String> {
+        data.into_iter().map(|row| row.unwrap().trade_id).collect()
+    }
+
+    #[test]
+    fn test_csv_closure_filter() {
+        let dir = temp_dir("filter");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ID,TRADE_TIME\nT1,1\nT2,2\nT3,3\nT4,4\nT5,5\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+
+        // 捕获集合
+        let accounts = ["T2", "T4"].into_iter().map(String::from).collect::<HashSet<_>>();
+        let (cert_key1, recv_chan1) = reader.subscribe_filter(Box::new(move |data: &TestTradeStruct| accounts.contains(&data.trade_id))).detach();
+        assert_eq!(ids(recv_chan1.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T2", "T4"]);
+
+        // 捕获共享配置, 修改后对之后的数据生效
+        let min_time = Arc::new(AtomicI32::new(3));
+        let config = min_time.clone();
+        let (cert_key2, recv_chan2) = reader.subscribe_filter(Box::new(move |data: &TestTradeStruct| data.trade_time >= config.load(Ordering::Relaxed))).detach();
+        assert_eq!(ids(recv_chan2.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T3", "T4", "T5"]);
+
+        // 原有的fn + verify_data方式
+        let (cert_key3, recv_chan3) = reader.subscribe("T5", |verify_data, data| data.trade_id == verify_data).detach();
+        assert_eq!(ids(recv_chan3.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T5"]);
+
+        min_time.store(7, Ordering::Relaxed);
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"T6,6\nT4,7\n").unwrap();
+        reader.notify_meta.sender.send(NotifyEventData {
+            event: NotifyEvent::ScheduleEvent,
+            last_notify_time: get_coarse_timestamp_ms(),
+        }).unwrap();
+        assert_eq!(ids(recv_chan1.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T4"]);
+        assert_eq!(ids(recv_chan2.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T4"]);
+        assert!(recv_chan3.recv_timeout(Duration::from_millis(200)).is_err());
+
+        for cert_key in [cert_key1, cert_key2, cert_key3] {
+            let _ = reader.unsubscribe(cert_key);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,127 @@
+#[allow(unused_imports)]
+mod test {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crossbeam::channel::Receiver;
+    use crate::reader::error::ReadResult;
+    use crate::reader::backpressure::{OverflowPolicy, SubscribeOptions};
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ACC")]
+        trade_acc: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    // 收到的(账号, 时间), 错误行记为("ERR", 0)
+    #[allow(dead_code)]
+    fn recv_rows(recv_chan: &Receiver<Vec<ReadResult<TestTradeStruct>>>) -> Vec<(String, i32)> {
+        recv_chan.recv_timeout(Duration::from_secs(5)).unwrap().into_iter().map(|row| match row {
+            Ok(data) => (data.trade_acc, data.trade_time),
+            Err(_) => ("ERR".to_string(), 0),
+        }).collect()
+    }
+
+    #[allow(dead_code)]
+    fn rows(expect: &[(&str, i32)]) -> Vec<(String, i32)> {
+        expect.iter().map(|(acc, time)| (acc.to_string(), *time)).collect()
+    }
+
+    #[test]
+    fn test_csv_key_routing() {
+        let dir = temp_dir("key_routing");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ACC,TRADE_TIME\nA,1\nB,2\nA,3\nC,4\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+
+        assert!(reader.subscribe_keys(&["A"]).is_err()); // 未设置key提取函数
+        reader.set_key_fn(|data| data.trade_acc.as_str()).unwrap();
+        assert!(reader.set_key_fn(|data| data.trade_acc.as_str()).is_err());
+
+        // 按key订阅与按过滤条件订阅同时使用, 注册前数据同样按key过滤
+        let (cert_a, recv_a) = reader.subscribe_keys(&["A"]).unwrap().detach();
+        assert_eq!(recv_rows(&recv_a), rows(&[("A", 1), ("A", 3)]));
+        let (cert_bc, recv_bc) = reader.subscribe_keys(&["B", "C"]).unwrap().detach();
+        assert_eq!(recv_rows(&recv_bc), rows(&[("B", 2), ("C", 4)]));
+        let (cert_late, recv_late) = reader.subscribe_filter(Box::new(|data: &TestTradeStruct| data.trade_time >= 3)).detach();
+        assert_eq!(recv_rows(&recv_late), rows(&[("A", 3), ("C", 4)]));
+
+        append(&reader, &path, "B,5\nA,6\nD,7\n");
+        assert_eq!(recv_rows(&recv_a), rows(&[("A", 6)]));
+        assert_eq!(recv_rows(&recv_bc), rows(&[("B", 5)]));
+        assert_eq!(recv_rows(&recv_late), rows(&[("B", 5), ("A", 6), ("D", 7)]));
+
+        // 取消订阅后不再路由; 错误行发给所有订阅者
+        let _ = reader.unsubscribe(cert_a);
+        append(&reader, &path, "A,8\nC,bad\n");
+        assert_eq!(recv_rows(&recv_bc), rows(&[("ERR", 0)]));
+        assert_eq!(recv_rows(&recv_late), rows(&[("A", 8), ("ERR", 0)]));
+        assert!(recv_a.recv_timeout(Duration::from_millis(200)).is_err());
+
+        let _ = reader.unsubscribe(cert_bc);
+        let _ = reader.unsubscribe(cert_late);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_csv_key_routing_with_options() {
+        let dir = temp_dir("key_routing_options");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ACC,TRADE_TIME\nA,1\nB,2\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+        reader.set_key_fn(|data| data.trade_acc.as_str()).unwrap();
+
+        // 不消费的按key订阅者按溢出策略丢弃, 不阻塞读线程
+        let options = SubscribeOptions::default().with_capacity(1).with_overflow(OverflowPolicy::DropNewest);
+        let (slow_cert, slow_chan) = reader.subscribe_keys_with(&["A"], options).unwrap().detach();
+        let (fast_cert, fast_chan) = reader.subscribe_keys(&["A"]).unwrap().detach();
+        assert_eq!(recv_rows(&fast_chan), rows(&[("A", 1)]));
+        for i in 3..6 {
+            append(&reader, &path, &format!("A,{}\n", i));
+            assert_eq!(recv_rows(&fast_chan), rows(&[("A", i)]));
+        }
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while reader.overflow_stats(slow_cert).unwrap().dropped_batches < 3 && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(reader.overflow_stats(slow_cert).unwrap().dropped_rows, 3);
+        assert_eq!(recv_rows(&slow_chan), rows(&[("A", 1)]));
+
+        let _ = reader.unsubscribe(fast_cert);
+        let _ = reader.unsubscribe(slow_cert);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_csv_key_routing_duplicate_keys() {
+        let dir = temp_dir("key_routing_duplicate");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ACC,TRADE_TIME\nA,1\nB,2\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+        reader.set_key_fn(|data| data.trade_acc.as_str()).unwrap();
+
+        // 重复的key只投递一次
+        let (cert_key, recv_chan) = reader.subscribe_keys(&["A", "A"]).unwrap().detach();
+        assert_eq!(recv_rows(&recv_chan), rows(&[("A", 1)]));
+        append(&reader, &path, "A,3\n");
+        assert_eq!(recv_rows(&recv_chan), rows(&[("A", 3)]));
+
+        reader.update_keys(cert_key, &["A", "B", "B", "A"]).unwrap();
+        append(&reader, &path, "A,4\nB,5\n");
+        assert_eq!(recv_rows(&recv_chan), rows(&[("A", 4), ("B", 5)]));
+        assert!(recv_chan.recv_timeout(Duration::from_millis(200)).is_err());
+
+        let _ = reader.unsubscribe(cert_key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
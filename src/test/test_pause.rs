@@ -0,0 +1,113 @@
+#[allow(unused_imports)]
+mod test {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crossbeam::channel::Receiver;
+    use crate::reader::error::ReadResult;
+    use crate::reader::pause::PauseMode;
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    // 接收指定行数的成交编号, 可能分多个批次
+    #[allow(dead_code)]
+    fn recv_ids(recv_chan: &Receiver<Vec<ReadResult<TestTradeStruct>>>, count: usize) -> Vec<String> {
+        let mut received = vec![];
+        while received.len() < count {
+            let batch = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+            received.extend(batch.into_iter().map(|row| row.unwrap().trade_id));
+        }
+        received
+    }
+
+    #[test]
+    fn test_csv_pause_resume() {
+        let dir = temp_dir("pause");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ID,TRADE_TIME\nT1,1\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+
+        let (cert_key1, recv_chan1) = reader.subscribe_filter(Box::new(|_| true)).detach();
+        assert_eq!(recv_ids(&recv_chan1, 1), vec!["T1"]);
+        let (cert_key2, recv_chan2) = reader.subscribe_filter(Box::new(|_| true)).detach();
+        assert_eq!(recv_ids(&recv_chan2, 1), vec!["T1"]);
+
+        assert!(reader.resume(cert_key1).is_err()); // 未暂停
+        assert!(reader.pause(-1, PauseMode::Reread).is_err()); // 未订阅
+
+        // 缓存模式: 暂停期间不影响其他订阅者, 恢复后先收到缓存的数据
+        reader.pause(cert_key1, PauseMode::Buffer(10)).unwrap();
+        assert!(reader.pause(cert_key1, PauseMode::Buffer(10)).is_err());
+        append(&reader, &path, "T2,2\n");
+        assert_eq!(recv_ids(&recv_chan2, 1), vec!["T2"]);
+        append(&reader, &path, "T3,3\n");
+        assert_eq!(recv_ids(&recv_chan2, 1), vec!["T3"]);
+        assert!(recv_chan1.is_empty());
+        reader.resume(cert_key1).unwrap();
+        append(&reader, &path, "T4,4\n");
+        assert_eq!(recv_ids(&recv_chan1, 3), vec!["T2", "T3", "T4"]);
+        assert_eq!(recv_ids(&recv_chan2, 1), vec!["T4"]);
+
+        // 超出缓存上限后改为从第一条未发送行重读
+        reader.pause(cert_key1, PauseMode::Buffer(1)).unwrap();
+        append(&reader, &path, "T5,5\nT6,6\n");
+        assert_eq!(recv_ids(&recv_chan2, 2), vec!["T5", "T6"]);
+        reader.resume(cert_key1).unwrap();
+        append(&reader, &path, "T7,7\n");
+        assert_eq!(recv_ids(&recv_chan1, 3), vec!["T5", "T6", "T7"]);
+
+        // 重读模式
+        reader.pause(cert_key1, PauseMode::Reread).unwrap();
+        append(&reader, &path, "T8,8\n");
+        assert_eq!(recv_ids(&recv_chan2, 2), vec!["T7", "T8"]);
+        reader.resume(cert_key1).unwrap();
+        assert_eq!(recv_ids(&recv_chan1, 1), vec!["T8"]);
+
+        let _ = reader.unsubscribe(cert_key1);
+        let _ = reader.unsubscribe(cert_key2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_csv_pause_replay_overflow() {
+        let dir = temp_dir("pause_replay");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ID,TRADE_TIME\nT1,1\nT2,2\nT3,3\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+        let (cert_key1, recv_chan1) = reader.subscribe_filter(Box::new(|_| true)).detach();
+        assert_eq!(recv_ids(&recv_chan1, 3), vec!["T1", "T2", "T3"]);
+        let (cert_key2, recv_chan2) = reader.subscribe_filter(Box::new(|_| true)).detach();
+        assert_eq!(recv_ids(&recv_chan2, 3), vec!["T1", "T2", "T3"]);
+
+        // 暂停期间的回放同样缓存; 超出上限后从暂停期间最早的读取起点重读, 已读取的T1不重复发送
+        reader.pause(cert_key1, PauseMode::Buffer(2)).unwrap();
+        reader.replay(cert_key1, ReplayFrom::Row(1)).unwrap();
+        append(&reader, &path, "T4,4\n");
+        assert_eq!(recv_ids(&recv_chan2, 1), vec!["T4"]);
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(recv_chan1.is_empty());
+        reader.resume(cert_key1).unwrap();
+        assert_eq!(recv_ids(&recv_chan1, 3), vec!["T2", "T3", "T4"]);
+        append(&reader, &path, "T5,5\n");
+        assert_eq!(recv_ids(&recv_chan1, 1), vec!["T5"]);
+        assert!(recv_chan1.recv_timeout(Duration::from_millis(200)).is_err());
+
+        let _ = reader.unsubscribe(cert_key1);
+        let _ = reader.unsubscribe(cert_key2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,79 @@
+#[allow(unused_imports)]
+mod test {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use std::error::Error;
+    use crossbeam::channel::Receiver;
+
+    use crate::reader::manager::*;
+    use crate::reader::error::{ReadError, ReadResult};
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    #[allow(dead_code)]
+    fn append_bytes(path: &PathBuf, bytes: &[u8]) {
+        let mut fd = std::fs::OpenOptions::new().create(true).append(true).open(path).unwrap();
+        fd.write_all(bytes).unwrap();
+    }
+
+    #[allow(dead_code)]
+    fn recv_batch(recv_chan: &Receiver<Vec<ReadResult<TestTradeStruct>>>) -> Vec<ReadResult<TestTradeStruct>> {
+        recv_chan.recv_timeout(Duration::from_secs(5)).unwrap()
+    }
+
+    #[test]
+    fn test_csv_read_error() {
+        let path = temp_path("read_error", "csv");
+        let head = "TRADE_ID,TRADE_TIME\nT1,93000\n";
+        append_bytes(&path, format!("{}T2,abc\nT3,93200\n", head).as_bytes());
+
+        let reader = get_or_create_csv_reader::<TestTradeStruct>(&path, true, EncType::UTF8).unwrap();
+        let (cert_a, recv_a) = reader.subscribe("", |_, _| true).detach();
+        let (cert_b, recv_b) = reader.subscribe("T1", |verify, data| data.trade_id == verify).detach();
+        let batch = recv_batch(&recv_a);
+        assert_eq!(batch.len(), 3);
+        assert!(batch[0].is_ok() && batch[2].is_ok());
+        let err = batch[1].as_ref().unwrap_err();
+        assert!(matches!(err, ReadError::Deserialize { .. }));
+        assert_eq!(err.path().map(|p| p.as_ref()), Some(&path));
+        assert_eq!(err.offset(), Some(head.len() as u64));
+        assert_eq!(err.line(), Some(3));
+        assert_eq!(err.field(), Some("TRADE_TIME"));
+        assert_eq!(err.raw(), Some("T2,abc"));
+        assert!(err.source().is_some());
+
+        // 错误行发给所有订阅者
+        let batch = recv_batch(&recv_b);
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[1].as_ref().unwrap_err().line(), Some(3));
+
+        // 增量读取: 非UTF8字节为解码错误
+        append_bytes(&path, b"T4,\xff\xfe\n");
+        reader.notify_meta.sender.send(NotifyEventData {
+            event: NotifyEvent::ScheduleEvent,
+            last_notify_time: get_coarse_timestamp_ms(),
+        }).unwrap();
+        let batch = recv_batch(&recv_a);
+        assert_eq!(batch.len(), 1);
+        let err = batch[0].as_ref().unwrap_err();
+        assert!(matches!(err, ReadError::Decode { .. }));
+        assert_eq!(err.line(), Some(5));
+        assert_eq!(err.field(), Some("TRADE_TIME"));
+
+        let _ = remove_csv_reader::<TestTradeStruct>(cert_b, false, &path);
+        let _ = remove_csv_reader::<TestTradeStruct>(cert_a, true, &path);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,129 @@
+#[allow(unused_imports)]
+mod test {
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use crossbeam::channel::Receiver;
+
+    use crate::reader::manager::*;
+    use crate::reader::subscribe_reader::ReplayFrom;
+    use crate::reader::dbf_reader::DbfFileState;
+    use crate::writer::csv_writer::CsvWriter;
+    use crate::writer::dbf_writer::DbfWriter;
+    use crate::reader::error::ReadResult;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    #[allow(dead_code)]
+    fn trade(i: i32) -> TestTradeStruct {
+        TestTradeStruct { trade_id: format!("成交{}", i), trade_time: 93000 + i * 100 }
+    }
+
+    #[allow(dead_code)]
+    fn recv_trades(recv_chan: &Receiver<Vec<ReadResult<TestTradeStruct>>>) -> Vec<TestTradeStruct> {
+        let data = recv_chan.recv_timeout(Duration::from_secs(5)).unwrap();
+        data.into_iter().map(|d| d.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_csv_replay() {
+        let path = temp_path("replay", "csv");
+        let writer = CsvWriter::<TestTradeStruct>::new(path.clone(), EncType::GBK);
+        writer.append(&trade(1)).unwrap();
+        let second_offset = std::fs::metadata(&path).unwrap().len();
+        writer.append_all(&[trade(2), trade(3)]).unwrap();
+
+        let reader = get_or_create_csv_reader::<TestTradeStruct>(&path, true, EncType::GBK).unwrap();
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(1), trade(2), trade(3)]);
+
+        reader.replay(cert_key, ReplayFrom::ByteOffset(second_offset)).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(2), trade(3)]);
+        // 偏移不在行首时从之后的第一行开始
+        reader.replay(cert_key, ReplayFrom::ByteOffset(second_offset - 1)).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(2), trade(3)]);
+        reader.replay(cert_key, ReplayFrom::ByteOffset(second_offset + 1)).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(3)]);
+        reader.replay(cert_key, ReplayFrom::ByteOffset(1)).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(1), trade(2), trade(3)]);
+        reader.replay(cert_key, ReplayFrom::ByteOffset(0)).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(1), trade(2), trade(3)]);
+        reader.replay(cert_key, ReplayFrom::Row(2)).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(3)]);
+        reader.replay(cert_key, ReplayFrom::Time { time_of: |t| t.trade_time as u64, since: 93150 }).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(2), trade(3)]);
+        assert!(reader.replay(-1, ReplayFrom::Row(0)).is_err());
+
+        // 回放后实时数据照常推送
+        writer.append(&trade(4)).unwrap();
+        reader.notify_meta.sender.send(NotifyEventData {
+            event: NotifyEvent::ScheduleEvent,
+            last_notify_time: get_coarse_timestamp_ms(),
+        }).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(4)]);
+
+        let _ = remove_csv_reader::<TestTradeStruct>(cert_key, true, &path);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_dbf_replay() {
+        let path = temp_path("replay", "dbf");
+        let writer = DbfWriter::<TestTradeStruct>::create_from_spec(path.clone(), "TRADE_ID C(20);TRADE_TIME I").unwrap();
+        writer.append_all(&[trade(1), trade(2), trade(3)]).unwrap();
+        let state = DbfFileState::load(&path).unwrap();
+
+        let reader = get_or_create_dbf_reader::<TestTradeStruct>(&path, true, EncType::GBK).unwrap();
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(1), trade(2), trade(3)]);
+
+        reader.replay(cert_key, ReplayFrom::ByteOffset(state.header_len + state.record_len)).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(2), trade(3)]);
+        reader.replay(cert_key, ReplayFrom::Row(2)).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(3)]);
+        reader.replay(cert_key, ReplayFrom::Time { time_of: |t| t.trade_time as u64, since: 93150 }).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(2), trade(3)]);
+
+        let _ = remove_dbf_reader::<TestTradeStruct>(cert_key, true, &path);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_dbf_replay_deleted_records() {
+        use std::io::{Seek, SeekFrom, Write};
+        let path = temp_path("replay_deleted", "dbf");
+        let writer = DbfWriter::<TestTradeStruct>::create_from_spec(path.clone(), "TRADE_ID C(20);TRADE_TIME I").unwrap();
+        writer.append_all(&[trade(1), trade(2), trade(3), trade(4)]).unwrap();
+        let state = DbfFileState::load(&path).unwrap();
+        // 标记第2条记录为已删除
+        let mut fd = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        fd.seek(SeekFrom::Start(state.header_len + state.record_len)).unwrap();
+        fd.write_all(b"*").unwrap();
+        drop(fd);
+
+        let reader = get_or_create_dbf_reader::<TestTradeStruct>(&path, true, EncType::GBK).unwrap();
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(1), trade(3), trade(4)]);
+
+        // 行序号按推送的行计数, 不含已删除记录
+        reader.replay(cert_key, ReplayFrom::Row(1)).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(3), trade(4)]);
+        reader.replay(cert_key, ReplayFrom::Row(2)).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(4)]);
+        reader.replay(cert_key, ReplayFrom::ByteOffset(state.header_len + state.record_len)).unwrap();
+        assert_eq!(recv_trades(&recv_chan), vec![trade(3), trade(4)]);
+
+        let _ = remove_dbf_reader::<TestTradeStruct>(cert_key, true, &path);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,81 @@
+#[allow(unused_imports)]
+mod test {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::reader::options::ReaderOptions;
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    const CONTENT: &str = "TRADE_ID,TRADE_TIME\nT1,1\nT2,2\nT3,3\nT4,4\nT5,5\n";
+
+    // 原地改写数据行(长度和文件身份不变), 从磁盘补发的行可以与内存中的行区分
+    #[allow(dead_code)]
+    fn rewrite_in_place(path: &PathBuf) {
+        let mut file = std::fs::OpenOptions::new().write(true).open(path).unwrap();
+        file.write_all(CONTENT.replace("\nT", "\nX").as_bytes()).unwrap();
+    }
+
+    #[allow(dead_code)]
+    fn ids(data: Vec<crate::reader::error::ReadResult<TestTradeStruct>>) -> Vec<String> {
+        data.into_iter().map(|row| row.unwrap().trade_id).collect()
+    }
+
+    #[test]
+    fn test_csv_retention_serves_late_subscriber_from_memory() {
+        let dir = temp_dir("retention_memory");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, CONTENT).unwrap();
+        let options = ReaderOptions::default().with_retention_rows(100);
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::with_options(path.clone(), true, EncType::UTF8, options).unwrap());
+
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        assert_eq!(ids(recv_chan.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T1", "T2", "T3", "T4", "T5"]);
+        assert_eq!(reader.retention.len(), 5);
+        assert_eq!(reader.retention.range(), (0, CONTENT.len() as u64));
+
+        rewrite_in_place(&path);
+        let (late_cert_key, late_recv_chan) = reader.subscribe("", |_, _| true).detach();
+        assert_eq!(ids(late_recv_chan.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T1", "T2", "T3", "T4", "T5"]);
+
+        let _ = reader.unsubscribe(late_cert_key);
+        let _ = reader.unsubscribe(cert_key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_csv_retention_falls_back_to_disk() {
+        let dir = temp_dir("retention_bounded");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, CONTENT).unwrap();
+        let options = ReaderOptions::default().with_retention_rows(3).with_retention_bytes(10);
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::with_options(path.clone(), true, EncType::UTF8, options).unwrap());
+
+        let (cert_key, recv_chan) = reader.subscribe("", |_, _| true).detach();
+        assert_eq!(ids(recv_chan.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T1", "T2", "T3", "T4", "T5"]);
+        // 每行5字节, 字节上限先生效
+        assert_eq!((reader.retention.len(), reader.retention.bytes()), (2, 10));
+
+        // 内存不足的部分从磁盘读取
+        rewrite_in_place(&path);
+        let (late_cert_key, late_recv_chan) = reader.subscribe_snapshot("", |_, _| true).unwrap().detach();
+        assert_eq!(ids(late_recv_chan.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["X1", "X2", "X3", "T4", "T5"]);
+
+        let _ = reader.unsubscribe(late_cert_key);
+        let _ = reader.unsubscribe(cert_key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
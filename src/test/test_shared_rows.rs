@@ -0,0 +1,61 @@
+#[allow(unused_imports)]
+mod test {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::reader::error::ReadResult;
+    use crate::reader::manager::get_or_create_csv_reader;
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::Deserialize;
+    use crate::test::common::*;
+
+    // 未实现Clone
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct WideOrder {
+        #[serde(rename = "ORDER_ID")]
+        order_id: String,
+        #[serde(rename = "TRADE_ACC")]
+        trade_acc: String,
+        #[serde(rename = "PRICE")]
+        price: f64,
+    }
+
+    #[allow(dead_code)]
+    fn rows(data: Vec<ReadResult<Arc<WideOrder>>>) -> Vec<Arc<WideOrder>> {
+        data.into_iter().map(|row| row.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_csv_shared_rows() {
+        let dir = temp_dir("shared_rows");
+        let path = dir.join("order.csv");
+        std::fs::write(&path, "ORDER_ID,TRADE_ACC,PRICE\nO1,A,1.5\nO2,B,2.5\n").unwrap();
+        let reader: Arc<SharedCsvReader<WideOrder>> = get_or_create_csv_reader(&path, true, EncType::UTF8).unwrap();
+
+        let (cert_key1, recv_chan1) = reader.subscribe_filter(Box::new(|_| true)).detach();
+        let first = rows(recv_chan1.recv_timeout(Duration::from_secs(5)).unwrap());
+        assert_eq!(first.iter().map(|row| row.order_id.as_str()).collect::<Vec<_>>(), vec!["O1", "O2"]);
+        let (cert_key2, recv_chan2) = reader.subscribe_filter(Box::new(|order: &Arc<WideOrder>| order.trade_acc == "A")).detach();
+        assert_eq!(rows(recv_chan2.recv_timeout(Duration::from_secs(5)).unwrap())[0].price, 1.5);
+
+        // 同一行发给多个订阅者时共享同一份数据
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"O3,A,3.5\n").unwrap();
+        reader.notify_meta.sender.send(NotifyEventData {
+            event: NotifyEvent::ScheduleEvent,
+            last_notify_time: get_coarse_timestamp_ms(),
+        }).unwrap();
+        let row1 = rows(recv_chan1.recv_timeout(Duration::from_secs(5)).unwrap()).remove(0);
+        let row2 = rows(recv_chan2.recv_timeout(Duration::from_secs(5)).unwrap()).remove(0);
+        assert!(Arc::ptr_eq(&row1, &row2));
+        assert_eq!(*row1, WideOrder { order_id: "O3".to_string(), trade_acc: "A".to_string(), price: 3.5 });
+
+        let _ = reader.unsubscribe(cert_key1);
+        let _ = reader.unsubscribe(cert_key2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
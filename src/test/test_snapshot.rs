@@ -0,0 +1,84 @@
+#[allow(unused_imports)]
+mod test {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::Duration;
+
+    use crossbeam::channel::Receiver;
+    use crate::reader::error::ReadResult;
+    use crate::reader::subscription::Subscription;
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    // 持续接收直到收到total行或超时
+    #[allow(dead_code)]
+    fn collect(recv_chan: Subscription<TestTradeStruct>, total: usize) -> std::thread::JoinHandle<Vec<i32>> {
+        std::thread::spawn(move || {
+            let mut received = vec![];
+            while received.len() < total {
+                match recv_chan.recv_timeout(Duration::from_secs(10)) {
+                    Ok(data) => received.extend(data.into_iter().map(|row| row.unwrap().trade_time)),
+                    Err(_) => break,
+                }
+            }
+            received
+        })
+    }
+
+    #[test]
+    fn test_csv_snapshot_handoff_under_concurrent_writes() {
+        const TOTAL: i32 = 600;
+        let dir = temp_dir("snapshot");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ID,TRADE_TIME\nT0,0\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+
+        let written = Arc::new(AtomicU64::new(1));
+        let writer = {
+            let (reader, path, written) = (reader.clone(), path.clone(), written.clone());
+            std::thread::spawn(move || {
+                let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+                for i in 1..TOTAL {
+                    file.write_all(format!("T{},{}\n", i, i).as_bytes()).unwrap();
+                    written.store(i as u64 + 1, Ordering::Release);
+                    if i % 5 == 0 {
+                        notify(&reader);
+                        std::thread::sleep(Duration::from_millis(1));
+                    }
+                }
+                notify(&reader);
+            })
+        };
+
+        // 写入过程中不同时刻订阅, 每个订阅者都应收到完整且不重复的0..TOTAL
+        let mut collectors = vec![];
+        for subscribe_at in [0, 100, 250, 400, 550] {
+            while written.load(Ordering::Acquire) < subscribe_at {
+                std::thread::yield_now();
+            }
+            let recv_chan = reader.subscribe_snapshot("", |_, _| true).unwrap();
+            collectors.push(collect(recv_chan, TOTAL as usize));
+        }
+        writer.join().unwrap();
+
+        for collector in collectors {
+            assert_eq!(collector.join().unwrap(), (0..TOTAL).collect::<Vec<_>>());
+        }
+        reader.stop();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,93 @@
+#[allow(unused_imports)]
+mod test {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crossbeam::channel::Receiver;
+    use crate::reader::options::ReaderOptions;
+    use crate::reader::stream::StreamItem;
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    // 跳过心跳, 取下一个推送项
+    #[allow(dead_code)]
+    fn next_item(recv_chan: &Receiver<StreamItem<TestTradeStruct>>, timeout: Duration) -> Option<StreamItem<TestTradeStruct>> {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            match recv_chan.recv_deadline(deadline) {
+                Ok(StreamItem::Heartbeat { .. }) => continue,
+                Ok(item) => return Some(item),
+                Err(_) => return None,
+            }
+        }
+    }
+
+    #[allow(dead_code)]
+    fn next_data(recv_chan: &Receiver<StreamItem<TestTradeStruct>>) -> Vec<(String, i32)> {
+        match next_item(recv_chan, Duration::from_secs(5)) {
+            Some(StreamItem::Data(rows)) => rows.into_iter().map(|row| {
+                let row = row.unwrap();
+                (row.trade_id, row.trade_time)
+            }).collect(),
+            other => panic!("expect data, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_csv_stream_control_events() {
+        let dir = temp_dir("stream");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ID,TRADE_TIME\nT1,1\nT2,2\n").unwrap();
+        let options = ReaderOptions::default().with_heartbeat(Duration::from_millis(200));
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::with_options(path.clone(), true, EncType::UTF8, options).unwrap());
+
+        let recv_chan = reader.subscribe_stream("", |_, _| true);
+        assert_eq!(next_data(&recv_chan), vec![("T1".to_string(), 1), ("T2".to_string(), 2)]);
+        assert!(matches!(next_item(&recv_chan, Duration::from_secs(5)), Some(StreamItem::CaughtUp { pos: 30 })));
+
+        // 无新数据的通知不触发重置
+        notify(&reader);
+        assert!(next_item(&recv_chan, Duration::from_millis(500)).is_none());
+
+        // 心跳
+        assert!(matches!(recv_chan.recv_timeout(Duration::from_secs(1)), Ok(StreamItem::Heartbeat { pos: 30, .. })));
+
+        // 截断并改变表头(按列序反序列化): FileReset -> SchemaChanged -> 从头推送
+        std::fs::write(&path, "ID,TIME\nT3,3\n").unwrap();
+        notify(&reader);
+        assert!(matches!(next_item(&recv_chan, Duration::from_secs(5)), Some(StreamItem::FileReset { .. })));
+        match next_item(&recv_chan, Duration::from_secs(5)) {
+            Some(StreamItem::SchemaChanged { fields }) => assert_eq!(fields, vec!["ID", "TIME"]),
+            other => panic!("expect schema changed, got {:?}", other),
+        }
+        assert_eq!(next_data(&recv_chan), vec![("T3".to_string(), 3)]);
+
+        // 替换为新文件: FileRotated -> SchemaChanged -> 从头推送新文件
+        std::fs::remove_file(&path).unwrap();
+        std::fs::write(&path, "TRADE_ID,TRADE_TIME\nT4,4\nT5,5\n").unwrap();
+        notify(&reader);
+        match next_item(&recv_chan, Duration::from_secs(5)) {
+            Some(StreamItem::FileRotated { old, new }) => assert_ne!(old, new),
+            other => panic!("expect file rotated, got {:?}", other),
+        }
+        assert!(matches!(next_item(&recv_chan, Duration::from_secs(5)), Some(StreamItem::SchemaChanged { .. })));
+        assert_eq!(next_data(&recv_chan), vec![("T4".to_string(), 4), ("T5".to_string(), 5)]);
+
+        reader.stop();
+        assert!(matches!(next_item(&recv_chan, Duration::from_secs(5)), Some(StreamItem::ReaderStopped)));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
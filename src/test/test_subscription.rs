@@ -0,0 +1,76 @@
+#[allow(unused_imports)]
+mod test {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    use crate::reader::error::ReadResult;
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ID")]
+        trade_id: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    #[allow(dead_code)]
+    fn ids(data: Vec<ReadResult<TestTradeStruct>>) -> Vec<String> {
+        data.into_iter().map(|row| row.unwrap().trade_id).collect()
+    }
+
+    #[test]
+    fn test_csv_subscription_handle() {
+        let dir = temp_dir("subscription");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ID,TRADE_TIME\nT1,1\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+
+        let sub1 = reader.subscribe("", |_, _| true);
+        assert_eq!(ids(sub1.recv_timeout(Duration::from_secs(5)).unwrap()), vec!["T1"]);
+        let sub2 = reader.subscribe("T1", |verify, data| data.trade_id == verify);
+        let sub3 = reader.subscribe_filter(Box::new(|_| true));
+        let (cert_key1, cert_key2) = (sub1.cert_key(), sub2.cert_key());
+
+        // drop即取消订阅
+        drop(sub1);
+        assert!(!reader.msg_dispatcher.contains(cert_key1));
+        assert!(reader.is_running.load(Ordering::Relaxed));
+
+        // 仍有其他订阅者时unsubscribe返回Ok; 已取消的再close返回Err
+        assert!(reader.unsubscribe(cert_key2).is_ok());
+        assert!(sub2.close().is_err());
+        assert!(reader.unsubscribe(cert_key2).is_err());
+
+        // detach后不再自动取消订阅
+        let (cert_key4, recv_chan4) = reader.subscribe("", |_, _| true).detach();
+        assert!(reader.msg_dispatcher.contains(cert_key4));
+
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"T2,2\n").unwrap();
+        reader.notify_meta.sender.send(NotifyEventData {
+            event: NotifyEvent::ScheduleEvent,
+            last_notify_time: get_coarse_timestamp_ms(),
+        }).unwrap();
+        for recv_chan in [sub3.receiver(), &recv_chan4] { // 注册前数据的补发可能与T2分开或合并推送
+            let mut received = vec![];
+            while received.last().map(String::as_str) != Some("T2") {
+                received.extend(ids(recv_chan.recv_timeout(Duration::from_secs(5)).unwrap()));
+            }
+        }
+
+        // 最后一个订阅者取消后停止扫单
+        assert!(sub3.close().is_ok());
+        assert!(reader.is_running.load(Ordering::Relaxed));
+        assert!(reader.unsubscribe(cert_key4).is_ok());
+        assert!(!reader.is_running.load(Ordering::Relaxed));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,74 @@
+#[allow(unused_imports)]
+mod test {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crossbeam::channel::Receiver;
+    use crate::reader::error::ReadResult;
+    use crate::reader::subscribe_reader::*;
+    use crate::common::model::*;
+    use crate::common::timer::get_coarse_timestamp_ms;
+    use serde::{Deserialize, Serialize};
+    use crate::test::common::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+    struct TestTradeStruct {
+        #[serde(rename = "TRADE_ACC")]
+        trade_acc: String,
+        #[serde(rename = "TRADE_TIME")]
+        trade_time: i32,
+    }
+
+    // 收到的(账号, 时间)
+    #[allow(dead_code)]
+    fn recv_rows(recv_chan: &Receiver<Vec<ReadResult<TestTradeStruct>>>) -> Vec<(String, i32)> {
+        recv_chan.recv_timeout(Duration::from_secs(5)).unwrap().into_iter()
+            .map(|row| row.map(|data| (data.trade_acc, data.trade_time)).unwrap())
+            .collect()
+    }
+
+    #[allow(dead_code)]
+    fn rows(expect: &[(&str, i32)]) -> Vec<(String, i32)> {
+        expect.iter().map(|(acc, time)| (acc.to_string(), *time)).collect()
+    }
+
+    #[test]
+    fn test_csv_update_filter() {
+        let dir = temp_dir("update_filter");
+        let path = dir.join("trade.csv");
+        std::fs::write(&path, "TRADE_ACC,TRADE_TIME\nA,1\nB,2\n").unwrap();
+        let reader = Arc::new(CsvReader::<TestTradeStruct>::new(path.clone(), true, EncType::UTF8).unwrap());
+        reader.set_key_fn(|data| data.trade_acc.as_str()).unwrap();
+
+        let sub_verify = reader.subscribe("A", |verify, data| data.trade_acc == verify);
+        assert_eq!(recv_rows(&sub_verify), rows(&[("A", 1)]));
+        let sub_keys = reader.subscribe_keys(&["A"]).unwrap();
+        assert_eq!(recv_rows(&sub_keys), rows(&[("A", 1)]));
+
+        // 更新后从下一批次生效, 不重新补发注册前数据
+        sub_verify.update_verify("B", |verify, data| data.trade_acc == verify).unwrap();
+        sub_keys.update_keys(&["B", "C"]).unwrap();
+        append(&reader, &path, "A,3\nB,4\nC,5\n");
+        assert_eq!(recv_rows(&sub_verify), rows(&[("B", 4)]));
+        assert_eq!(recv_rows(&sub_keys), rows(&[("B", 4), ("C", 5)]));
+
+        // 按key订阅与按过滤条件订阅可互相转换
+        reader.update_filter(sub_keys.cert_key(), Box::new(|data: &TestTradeStruct| data.trade_time > 6)).unwrap();
+        reader.update_keys(sub_verify.cert_key(), &["A"]).unwrap();
+        append(&reader, &path, "A,6\nB,7\n");
+        assert_eq!(recv_rows(&sub_verify), rows(&[("A", 6)]));
+        assert_eq!(recv_rows(&sub_keys), rows(&[("B", 7)]));
+        assert!(sub_verify.try_recv().is_err());
+        assert!(sub_keys.try_recv().is_err());
+
+        // 已取消的订阅更新返回Err
+        let cert_key = sub_keys.cert_key();
+        drop(sub_keys);
+        assert!(reader.update_keys(cert_key, &["A"]).is_err());
+        assert!(reader.update_verify(cert_key, "A", |verify, data| data.trade_acc == verify).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
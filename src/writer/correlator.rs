@@ -0,0 +1,163 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use crossbeam::channel::{bounded, Receiver, RecvTimeoutError, Sender};
+use dashmap::DashMap;
+use serde::de::DeserializeOwned;
+use anyhow::{Result, bail};
+use super::RecordWriter;
+use crate::common::model::FileType;
+use crate::reader::subscribe_reader::{ReadRunner, SubsReader};
+use crate::reader::subscription::Subscription;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CorrelateError {
+    Timeout, // 超时未收到应答
+    Disconnected, // 关联器已关闭
+}
+
+impl std::fmt::Display for CorrelateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CorrelateError::Timeout => write!(f, "wait response timeout"),
+            CorrelateError::Disconnected => write!(f, "correlator closed"),
+        }
+    }
+}
+
+impl std::error::Error for CorrelateError {}
+
+type PendingMap<K, Rsp> = Arc<DashMap<K, (u64, Sender<Rsp>)>>; // 关联键 -> (请求序号, 应答通道)
+
+/*
+PendingResponse: 等待中的应答; drop时从pending中移除, 不再等待
+*/
+pub struct PendingResponse<K: Eq + Hash, Rsp> {
+    pub key: K, // 关联键(如客户订单号)
+    id: u64, // 请求序号, 区分同一关联键先后提交的请求
+    receiver: Receiver<Rsp>,
+    pending: PendingMap<K, Rsp>,
+}
+
+impl<K: Eq + Hash, Rsp> PendingResponse<K, Rsp> {
+    /// 阻塞等待应答, 超时后不再等待该请求
+    pub fn wait(self, timeout: Duration) -> std::result::Result<Rsp, CorrelateError> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(rsp) => Ok(rsp),
+            Err(RecvTimeoutError::Timeout) => {
+                self.unregister();
+                // 移除前应答可能刚好到达
+                self.receiver.try_recv().map_err(|_| CorrelateError::Timeout)
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(CorrelateError::Disconnected),
+        }
+    }
+
+    /// 应答接收通道, 可用于select
+    pub fn receiver(&self) -> &Receiver<Rsp> {
+        &self.receiver
+    }
+
+    // 仅移除自身的登记, 同一关联键可能已被重新提交
+    fn unregister(&self) {
+        self.pending.remove_if(&self.key, |_, (id, _)| *id == self.id);
+    }
+}
+
+impl<K: Eq + Hash, Rsp> Drop for PendingResponse<K, Rsp> {
+    fn drop(&mut self) {
+        self.unregister();
+    }
+}
+
+/*
+Correlator: 请求/应答关联
+请求写入委托文件, 通过订阅应答文件按关联键匹配应答行;
+等待中的请求统一记录在pending中
+*/
+pub struct Correlator<Req, Rsp: DeserializeOwned + Clone + Send + Sync + 'static, K: Eq + Hash> {
+    writer: Box<dyn RecordWriter<Req>>,
+    request_key: fn(&Req) -> K,
+    pending: PendingMap<K, Rsp>,
+    next_id: AtomicU64, // 下一个请求序号
+    _subscription: Subscription<Rsp>, // drop时取消订阅, 应答线程随之退出
+}
+
+impl<Req, Rsp, K> Correlator<Req, Rsp, K>
+where
+    Rsp: DeserializeOwned + Clone + Send + Sync + 'static,
+    K: Eq + Hash + Clone + Debug + Send + Sync + 'static,
+{
+    /// 创建关联器: 请求通过writer写入, 应答从reader订阅, 两侧分别通过key函数提取关联键
+    pub fn new<F: FileType + 'static>(
+        writer: Box<dyn RecordWriter<Req>>,
+        reader: Arc<SubsReader<Rsp, F>>,
+        request_key: fn(&Req) -> K,
+        response_key: fn(&Rsp) -> K,
+    ) -> Self
+    where
+        SubsReader<Rsp, F>: ReadRunner + Send + Sync,
+    {
+        let pending: PendingMap<K, Rsp> = Arc::new(DashMap::new());
+        let subscription = reader.subscribe("", |_, _| true);
+        let (cert_key, recv_chan) = (subscription.cert_key(), subscription.receiver().clone());
+
+        let pending_clone = pending.clone();
+        std::thread::spawn(move || {
+            while let Ok(data_list) = recv_chan.recv() {
+                for data in data_list {
+                    match data {
+                        Ok(rsp) => {
+                            let key = response_key(&rsp);
+                            if let Some((_, (_, sender))) = pending_clone.remove(&key) {
+                                ::ftlog::debug!("correlate response key={:?}", key);
+                                let _ = sender.send(rsp);
+                            }
+                        }
+                        Err(e) => {
+                            ::ftlog::error!("correlator recv error data: {:?}", e);
+                        }
+                    }
+                }
+            }
+            ::ftlog::info!("correlator loop exit, cert={}", cert_key);
+            pending_clone.clear(); // 等待方收到Disconnected
+        });
+
+        Self {
+            writer,
+            request_key,
+            pending,
+            next_id: AtomicU64::new(0),
+            _subscription: subscription,
+        }
+    }
+
+    /// 写入请求并返回等待句柄; 同一关联键同时只能有一个等待中的请求
+    pub fn submit(&self, request: &Req) -> Result<PendingResponse<K, Rsp>> {
+        let key = (self.request_key)(request);
+        let (send_chan, recv_chan) = bounded(1);
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        match self.pending.entry(key.clone()) {
+            dashmap::Entry::Occupied(_) => bail!("request key {:?} already pending", key),
+            dashmap::Entry::Vacant(entry) => {
+                entry.insert((id, send_chan)); // 先登记再写入, 避免应答早于登记到达
+            }
+        }
+        let pending = PendingResponse { key, id, receiver: recv_chan, pending: self.pending.clone() };
+        self.writer.write_record(request)?; // 写入失败时随pending drop移除登记
+        Ok(pending)
+    }
+
+    /// 取消等待中的请求
+    pub fn cancel(&self, key: &K) -> bool {
+        self.pending.remove(key).is_some()
+    }
+
+    /// 等待中的请求数量
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+}
@@ -0,0 +1,117 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use anyhow::{Result, anyhow, bail};
+use serde::Serialize;
+use super::get_write_path_lock;
+use crate::common::model::EncType;
+
+/*
+CsvDialect: CSV格式约定(分隔符/引号/行结束符)
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct CsvDialect {
+    pub delimiter: u8, // 分隔符
+    pub quote: u8, // 引号
+    pub quote_style: csv::QuoteStyle, // 引号策略
+    pub terminator: csv::Terminator, // 行结束符
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+            quote_style: csv::QuoteStyle::Necessary,
+            terminator: csv::Terminator::CRLF,
+        }
+    }
+}
+
+/*
+CsvWriter: CSV追加写入
+每次追加在内存中编码为完整的行(以行结束符结尾), 再以一次写入追加到文件末尾,
+PB系统不会读到半行数据; 仅在文件不存在或为空时写入表头
+*/
+pub struct CsvWriter<T: Serialize> {
+    pub file_path: PathBuf, // 文件路径
+    pub enc_type: EncType, // 编码类型
+    pub dialect: CsvDialect, // 格式约定
+    pub sync_on_write: bool, // 每次写入后是否fsync
+    _phantom: std::marker::PhantomData<fn(&T)>,
+}
+
+impl<T: Serialize> CsvWriter<T> {
+    pub fn new(file_path: PathBuf, enc_type: EncType) -> Self {
+        Self {
+            file_path,
+            enc_type,
+            dialect: CsvDialect::default(),
+            sync_on_write: false,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// 设置格式约定
+    pub fn with_dialect(mut self, dialect: CsvDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// 设置每次写入后是否fsync
+    pub fn with_sync(mut self, sync_on_write: bool) -> Self {
+        self.sync_on_write = sync_on_write;
+        self
+    }
+
+    /// 追加单条记录
+    pub fn append(&self, record: &T) -> Result<()> {
+        self.append_all(std::slice::from_ref(record))
+    }
+
+    /// 追加多条记录(一次写入)
+    pub fn append_all(&self, records: &[T]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        let path_lock = get_write_path_lock(&self.file_path);
+        let _guard = path_lock.lock().map_err(|e| anyhow!("Failed to acquire path lock: {:?}", e))?;
+
+        let mut fd = OpenOptions::new().append(true).create(true).open(&self.file_path)?;
+        fd.lock()?; // 进程间独占锁, drop时释放
+        let is_new_file = fd.metadata()?.len() == 0;
+        let data = self.encode(records, is_new_file)?;
+        fd.write_all(&data)?;
+        if self.sync_on_write {
+            fd.sync_data()?;
+        }
+        ::ftlog::debug!("{} append {} records", self.file_path.display(), records.len());
+        Ok(())
+    }
+
+    // 编码为完整行, 按编码类型转换字节
+    fn encode(&self, records: &[T], with_header: bool) -> Result<Vec<u8>> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(self.dialect.delimiter)
+            .quote(self.dialect.quote)
+            .quote_style(self.dialect.quote_style)
+            .terminator(self.dialect.terminator)
+            .has_headers(with_header)
+            .from_writer(vec![]);
+        for record in records {
+            writer.serialize(record)?;
+        }
+        let data = writer.into_inner().map_err(|e| anyhow!("{}", e))?;
+        match self.enc_type {
+            EncType::UTF8 => Ok(data),
+            EncType::GBK => {
+                let data = String::from_utf8(data)?;
+                let (encoded, _, had_errors) = encoding_rs::GBK.encode(&data);
+                if had_errors {
+                    bail!("record contains characters not representable in {}", self.enc_type)
+                }
+                Ok(encoded.into_owned())
+            }
+        }
+    }
+}
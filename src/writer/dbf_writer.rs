@@ -0,0 +1,436 @@
+use std::convert::TryFrom;
+use std::fs::{File, OpenOptions};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use chrono::Datelike;
+use anyhow::{Result, anyhow, bail};
+use serde::Serialize;
+use serde::ser::{self, Impossible};
+use super::{get_write_path_lock, mark_self_write};
+use crate::reader::dbf_reader::DbfFileState;
+
+pub use dbase::FieldValue;
+
+const DBF_FILE_TERMINATOR: u8 = 0x1A; // 文件结束符
+const DBF_DELETED_FLAG: u8 = b'*'; // 记录删除标记
+const DBF_HEADER_DATE_OFFSET: u64 = 1; // 文件头中最后更新日期(3)+记录数(4)的偏移
+const DBF_MAX_CHAR_LEN: usize = 254; // 字符字段最大长度
+const DEFAULT_CHAR_LEN: usize = 50; // 推断字符字段时的默认长度
+const DEFAULT_NUMERIC_LEN: u8 = 20; // 推断数值字段时的默认长度
+const DEFAULT_NUMERIC_DECIMALS: u8 = 6; // 推断数值字段时的默认小数位数
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbfFieldKind {
+    Character, // C(len)
+    Numeric, // N(len,dec)
+    Float, // F(len,dec)
+    Logical, // L
+    Date, // D
+    Integer, // I
+}
+
+/*
+DbfFieldSpec: DBF字段定义
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DbfFieldSpec {
+    pub name: String, // 字段名(最长10个字符)
+    pub kind: DbfFieldKind,
+    pub length: u8, // 字段长度(仅C/N/F有效)
+    pub decimals: u8, // 小数位数(仅N/F有效)
+}
+
+impl DbfFieldSpec {
+    pub fn new(name: &str, kind: DbfFieldKind, length: u8, decimals: u8) -> Self {
+        Self { name: name.to_string(), kind, length, decimals }
+    }
+
+    /// 解析字段定义, 格式与python dbf库一致: `a N(10,0);b C(50);c N(13,3);d L;e D`
+    pub fn parse(specs: &str) -> Result<Vec<Self>> {
+        let mut fields = vec![];
+        for spec in specs.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+            let (name, type_def) = spec.split_once(char::is_whitespace)
+                .ok_or_else(|| anyhow!("invalid field spec: {}", spec))?;
+            let type_def = type_def.trim();
+            let params = match (type_def.find('('), type_def.rfind(')')) {
+                (Some(begin), Some(end)) if begin < end => type_def[begin + 1..end]
+                    .split(',')
+                    .map(|p| p.trim().parse::<u8>().map_err(|e| anyhow!("invalid field spec: {}, {}", spec, e)))
+                    .collect::<Result<Vec<_>>>()?,
+                _ => vec![],
+            };
+            let param = |idx: usize| params.get(idx).copied();
+            let field = match type_def.chars().next().map(|c| c.to_ascii_uppercase()) {
+                Some('C') => Self::new(name, DbfFieldKind::Character, param(0).ok_or_else(|| anyhow!("missing length: {}", spec))?, 0),
+                Some('N') => Self::new(name, DbfFieldKind::Numeric, param(0).ok_or_else(|| anyhow!("missing length: {}", spec))?, param(1).unwrap_or(0)),
+                Some('F') => Self::new(name, DbfFieldKind::Float, param(0).ok_or_else(|| anyhow!("missing length: {}", spec))?, param(1).unwrap_or(0)),
+                Some('L') => Self::new(name, DbfFieldKind::Logical, 1, 0),
+                Some('D') => Self::new(name, DbfFieldKind::Date, 8, 0),
+                Some('I') => Self::new(name, DbfFieldKind::Integer, 4, 0),
+                _ => bail!("unsupported field type: {}", spec),
+            };
+            fields.push(field);
+        }
+        if fields.is_empty() {
+            bail!("empty field spec")
+        }
+        Ok(fields)
+    }
+
+    /// 根据样例记录推断字段定义
+    ///
+    /// 字段顺序与结构体字段顺序一致(`#[serde(rename)]`生效);
+    /// String推断为C(max(50,样例长度)), f64为N(20,6), f32为F(20,6), i32为I, bool为L;
+    /// None无法推断类型, 返回错误
+    pub fn from_record<T: Serialize>(sample: &T) -> Result<Vec<Self>> {
+        let mut collector = SpecCollector { fields: vec![] };
+        sample.serialize(&mut collector).map_err(|e| anyhow!("{}", e))?;
+        if collector.fields.is_empty() {
+            bail!("no field inferred from record")
+        }
+        Ok(collector.fields)
+    }
+}
+
+// 按字段定义构建dbase writer
+fn table_builder(specs: &[DbfFieldSpec]) -> Result<dbase::TableWriterBuilder> {
+    let mut builder = dbase::TableWriterBuilder::new();
+    for spec in specs {
+        let name = dbase::FieldName::try_from(spec.name.as_str()).map_err(|e| anyhow!("invalid field name {}: {:?}", spec.name, e))?;
+        builder = match spec.kind {
+            DbfFieldKind::Character => builder.add_character_field(name, spec.length),
+            DbfFieldKind::Numeric => builder.add_numeric_field(name, spec.length, spec.decimals),
+            DbfFieldKind::Float => builder.add_float_field(name, spec.length, spec.decimals),
+            DbfFieldKind::Logical => builder.add_logical_field(name),
+            DbfFieldKind::Date => builder.add_date_field(name),
+            DbfFieldKind::Integer => builder.add_integer_field(name),
+        };
+    }
+    Ok(builder)
+}
+
+/*
+DbfWriter: DBF追加写入
+写入顺序: 记录数据 -> 结束符 -> fsync -> 文件头记录数 -> fsync,
+读取方(包括DbfReader)以文件头记录数为准, 不会读到写了一半的记录
+*/
+pub struct DbfWriter<T: Serialize> {
+    pub file_path: PathBuf, // 文件路径
+    _phantom: std::marker::PhantomData<fn(&T)>,
+}
+
+impl<T: Serialize> DbfWriter<T> {
+    /// 打开已存在的DBF文件, 字段定义沿用文件头
+    pub fn open(file_path: PathBuf) -> Result<Self> {
+        let state = DbfFileState::load(&file_path)?;
+        if state.header_len == 0 || state.record_len == 0 {
+            bail!("invalid dbf file: {}", file_path.display())
+        }
+        Ok(Self { file_path, _phantom: std::marker::PhantomData })
+    }
+
+    /// 文件不存在(或为空)时按字段定义创建; 已存在则沿用原字段定义追加
+    pub fn create(file_path: PathBuf, specs: &[DbfFieldSpec]) -> Result<Self> {
+        let path_lock = get_write_path_lock(&file_path);
+        let _guard = path_lock.lock().map_err(|e| anyhow!("Failed to acquire path lock: {:?}", e))?;
+
+        let mut fd = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&file_path)?;
+        fd.lock()?; // 进程间独占锁, drop时释放
+        if fd.metadata()?.len() == 0 {
+            let mut buf = Cursor::new(Vec::<u8>::new());
+            {
+                let mut writer = table_builder(specs)?.build_with_dest(&mut buf);
+                writer.finalize().map_err(|e| anyhow!("{}", e))?;
+            }
+            fd.write_all(buf.get_ref())?;
+            fd.sync_all()?;
+            ::ftlog::info!("create dbf file:{}", file_path.display());
+        }
+        drop(fd);
+        Self::open(file_path)
+    }
+
+    /// 按字段定义字符串创建, 见[`DbfFieldSpec::parse`]
+    pub fn create_from_spec(file_path: PathBuf, specs: &str) -> Result<Self> {
+        Self::create(file_path, &DbfFieldSpec::parse(specs)?)
+    }
+
+    /// 按样例记录推断字段定义创建, 见[`DbfFieldSpec::from_record`]
+    pub fn create_from_record(file_path: PathBuf, sample: &T) -> Result<Self> {
+        Self::create(file_path, &DbfFieldSpec::from_record(sample)?)
+    }
+
+    /// 文件头记录数
+    pub fn record_count(&self) -> Result<u64> {
+        Ok(DbfFileState::load(&self.file_path)?.num_records)
+    }
+
+    /// 追加单条记录, 返回该记录序号
+    pub fn append(&self, record: &T) -> Result<u64> {
+        Ok(self.append_all(std::slice::from_ref(record))? - 1)
+    }
+
+    /// 追加多条记录, 返回追加后的记录数
+    pub fn append_all(&self, records: &[T]) -> Result<u64> {
+        let path_lock = get_write_path_lock(&self.file_path);
+        let _guard = path_lock.lock().map_err(|e| anyhow!("Failed to acquire path lock: {:?}", e))?;
+
+        let mut fd = OpenOptions::new().read(true).write(true).open(&self.file_path)?;
+        fd.lock()?; // 进程间独占锁, drop时释放
+        let state = DbfFileState::read_from(&mut fd)?;
+        if records.is_empty() {
+            return Ok(state.num_records);
+        }
+
+        let data = encode_records(&mut fd, records, state.record_len)?;
+        let data_end = state.header_len + state.num_records * state.record_len;
+
+        // 1. 写记录及结束符(覆盖原结束符)
+        fd.seek(SeekFrom::Start(data_end))?;
+        fd.write_all(&data)?;
+        fd.write_all(&[DBF_FILE_TERMINATOR])?;
+        fd.set_len(data_end + data.len() as u64 + 1)?;
+        fd.sync_data()?;
+
+        // 2. 最后更新文件头记录数
+        let num_records = state.num_records + records.len() as u64;
+        write_header_count(&mut fd, num_records)?;
+        fd.sync_data()?;
+        ::ftlog::debug!("{} append {} records, total={}", self.file_path.display(), records.len(), num_records);
+        Ok(num_records)
+    }
+}
+
+/// 原地更新第index条记录的指定字段, 其余字段及文件头保持不变
+///
+/// 遵循写入方加锁约定; 若本进程同时在读该文件, 该次写回不会再次分发给订阅者
+pub fn update_dbf_record(file_path: &Path, index: u64, fields: &[(&str, FieldValue)]) -> Result<()> {
+    let path_lock = get_write_path_lock(file_path);
+    let _guard = path_lock.lock().map_err(|e| anyhow!("Failed to acquire path lock: {:?}", e))?;
+
+    let mut fd = OpenOptions::new().read(true).write(true).open(file_path)?;
+    fd.lock()?; // 进程间独占锁, drop时释放
+    let before = DbfFileState::read_from(&mut fd)?;
+    if index >= before.num_records {
+        bail!("record index {} out of range, records={}", index, before.num_records)
+    }
+    let record_begin = before.header_len + index * before.record_len;
+    let mut deletion_flag = [0u8; 1];
+    fd.seek(SeekFrom::Start(record_begin))?;
+    fd.read_exact(&mut deletion_flag)?;
+    if deletion_flag[0] == DBF_DELETED_FLAG {
+        bail!("record {} is deleted", index)
+    }
+
+    // 读取原记录, 替换指定字段后在内存中重新编码
+    fd.seek(SeekFrom::Start(0))?;
+    let mut reader = dbase::Reader::new(&mut fd).map_err(|e| anyhow!("{}", e))?;
+    let field_ranges = {
+        let mut offset = 1_u64; // 删除标记之后
+        reader.fields().iter().map(|f| {
+            let range = (f.name().to_string(), offset, f.length() as u64);
+            offset += f.length() as u64;
+            range
+        }).collect::<Vec<_>>()
+    };
+    reader.seek(index as usize).map_err(|e| anyhow!("{}", e))?;
+    let mut record = reader.iter_records().next()
+        .ok_or_else(|| anyhow!("record {} not found", index))?
+        .map_err(|e| anyhow!("{}", e))?;
+    for (name, value) in fields {
+        if record.insert(name.to_string(), value.clone()).is_none() {
+            bail!("field {} not found", name)
+        }
+    }
+    let mut buf = Cursor::new(Vec::<u8>::new());
+    {
+        let mut writer = dbase::TableWriterBuilder::from_reader(reader).build_with_dest(&mut buf);
+        writer.write_record(&record).map_err(|e| anyhow!("{}", e))?;
+        writer.finalize().map_err(|e| anyhow!("{}", e))?;
+    }
+    let bytes = buf.into_inner();
+    let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as u64;
+
+    // 仅写回被更新字段对应的字节
+    for (name, _) in fields {
+        let (_, offset, length) = field_ranges.iter().find(|(n, _, _)| n == name)
+            .ok_or_else(|| anyhow!("field {} not found", name))?;
+        let begin = (header_len + offset) as usize;
+        fd.seek(SeekFrom::Start(record_begin + offset))?;
+        fd.write_all(&bytes[begin..begin + *length as usize])?;
+    }
+    fd.sync_data()?;
+    let mut written = vec![0u8; before.record_len as usize];
+    fd.seek(SeekFrom::Start(record_begin))?;
+    fd.read_exact(&mut written)?;
+    mark_self_write(file_path, before.num_records, record_begin, &written);
+    ::ftlog::debug!("{} update record {}", file_path.display(), index);
+    Ok(())
+}
+
+// 更新文件头的最后更新日期及记录数
+pub(crate) fn write_header_count(fd: &mut File, num_records: u64) -> Result<()> {
+    let num_records = u32::try_from(num_records).map_err(|_| anyhow!("too many records: {}", num_records))?;
+    let today = chrono::Local::now().date_naive();
+    let mut buf = [0u8; 7];
+    buf[0] = (today.year() - 1900) as u8;
+    buf[1] = today.month() as u8;
+    buf[2] = today.day() as u8;
+    buf[3..7].copy_from_slice(&num_records.to_le_bytes());
+    fd.seek(SeekFrom::Start(DBF_HEADER_DATE_OFFSET))?;
+    fd.write_all(&buf)?;
+    Ok(())
+}
+
+// 按文件已有字段定义在内存中编码记录, 返回记录区字节
+fn encode_records<T: Serialize>(fd: &mut File, records: &[T], record_len: u64) -> Result<Vec<u8>> {
+    fd.seek(SeekFrom::Start(0))?;
+    let reader = dbase::Reader::new(&mut *fd).map_err(|e| anyhow!("{}", e))?;
+    let mut buf = Cursor::new(Vec::<u8>::new());
+    {
+        let mut writer = dbase::TableWriterBuilder::from_reader(reader).build_with_dest(&mut buf);
+        for record in records {
+            writer.write_record(record).map_err(|e| anyhow!("{}", e))?;
+        }
+        writer.finalize().map_err(|e| anyhow!("{}", e))?;
+    }
+    let bytes = buf.into_inner();
+    let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    let encoded_record_len = u16::from_le_bytes([bytes[10], bytes[11]]) as u64;
+    if encoded_record_len != record_len {
+        bail!("record length mismatch: file={}, encoded={}", record_len, encoded_record_len)
+    }
+    let data_len = records.len() * record_len as usize;
+    Ok(bytes[header_len..header_len + data_len].to_vec())
+}
+
+#[derive(Debug)]
+struct SpecError(String);
+
+impl std::fmt::Display for SpecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+impl ser::Error for SpecError {
+    fn custom<M: std::fmt::Display>(msg: M) -> Self {
+        SpecError(msg.to_string())
+    }
+}
+
+fn unsupported<O>(type_name: &str) -> std::result::Result<O, SpecError> {
+    Err(SpecError(format!("cannot infer dbf field from {}", type_name)))
+}
+
+// 收集结构体字段
+struct SpecCollector {
+    fields: Vec<DbfFieldSpec>,
+}
+
+impl ser::SerializeStruct for &mut SpecCollector {
+    type Ok = ();
+    type Error = SpecError;
+
+    fn serialize_field<V: ?Sized + Serialize>(&mut self, key: &'static str, value: &V) -> std::result::Result<(), SpecError> {
+        let (kind, length, decimals) = value.serialize(KindProbe)
+            .map_err(|e| SpecError(format!("field {}: {}", key, e)))?;
+        self.fields.push(DbfFieldSpec::new(key, kind, length, decimals));
+        Ok(())
+    }
+
+    fn end(self) -> std::result::Result<(), SpecError> {
+        Ok(())
+    }
+}
+
+impl ser::Serializer for &mut SpecCollector {
+    type Ok = ();
+    type Error = SpecError;
+    type SerializeSeq = Impossible<(), SpecError>;
+    type SerializeTuple = Impossible<(), SpecError>;
+    type SerializeTupleStruct = Impossible<(), SpecError>;
+    type SerializeTupleVariant = Impossible<(), SpecError>;
+    type SerializeMap = Impossible<(), SpecError>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), SpecError>;
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> std::result::Result<Self, SpecError> { Ok(self) }
+    fn serialize_bool(self, _v: bool) -> std::result::Result<(), SpecError> { unsupported("bool record") }
+    fn serialize_i8(self, _v: i8) -> std::result::Result<(), SpecError> { unsupported("i8 record") }
+    fn serialize_i16(self, _v: i16) -> std::result::Result<(), SpecError> { unsupported("i16 record") }
+    fn serialize_i32(self, _v: i32) -> std::result::Result<(), SpecError> { unsupported("i32 record") }
+    fn serialize_i64(self, _v: i64) -> std::result::Result<(), SpecError> { unsupported("i64 record") }
+    fn serialize_u8(self, _v: u8) -> std::result::Result<(), SpecError> { unsupported("u8 record") }
+    fn serialize_u16(self, _v: u16) -> std::result::Result<(), SpecError> { unsupported("u16 record") }
+    fn serialize_u32(self, _v: u32) -> std::result::Result<(), SpecError> { unsupported("u32 record") }
+    fn serialize_u64(self, _v: u64) -> std::result::Result<(), SpecError> { unsupported("u64 record") }
+    fn serialize_f32(self, _v: f32) -> std::result::Result<(), SpecError> { unsupported("f32 record") }
+    fn serialize_f64(self, _v: f64) -> std::result::Result<(), SpecError> { unsupported("f64 record") }
+    fn serialize_char(self, _v: char) -> std::result::Result<(), SpecError> { unsupported("char record") }
+    fn serialize_str(self, _v: &str) -> std::result::Result<(), SpecError> { unsupported("str record") }
+    fn serialize_bytes(self, _v: &[u8]) -> std::result::Result<(), SpecError> { unsupported("bytes record") }
+    fn serialize_none(self) -> std::result::Result<(), SpecError> { unsupported("none record") }
+    fn serialize_some<V: ?Sized + Serialize>(self, value: &V) -> std::result::Result<(), SpecError> { value.serialize(self) }
+    fn serialize_unit(self) -> std::result::Result<(), SpecError> { unsupported("unit record") }
+    fn serialize_unit_struct(self, _name: &'static str) -> std::result::Result<(), SpecError> { unsupported("unit struct record") }
+    fn serialize_unit_variant(self, _name: &'static str, _idx: u32, _variant: &'static str) -> std::result::Result<(), SpecError> { unsupported("enum record") }
+    fn serialize_newtype_struct<V: ?Sized + Serialize>(self, _name: &'static str, value: &V) -> std::result::Result<(), SpecError> { value.serialize(self) }
+    fn serialize_newtype_variant<V: ?Sized + Serialize>(self, _name: &'static str, _idx: u32, _variant: &'static str, _value: &V) -> std::result::Result<(), SpecError> { unsupported("enum record") }
+    fn serialize_seq(self, _len: Option<usize>) -> std::result::Result<Self::SerializeSeq, SpecError> { unsupported("seq record") }
+    fn serialize_tuple(self, _len: usize) -> std::result::Result<Self::SerializeTuple, SpecError> { unsupported("tuple record") }
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> std::result::Result<Self::SerializeTupleStruct, SpecError> { unsupported("tuple struct record") }
+    fn serialize_tuple_variant(self, _name: &'static str, _idx: u32, _variant: &'static str, _len: usize) -> std::result::Result<Self::SerializeTupleVariant, SpecError> { unsupported("enum record") }
+    fn serialize_map(self, _len: Option<usize>) -> std::result::Result<Self::SerializeMap, SpecError> { unsupported("map record") }
+    fn serialize_struct_variant(self, _name: &'static str, _idx: u32, _variant: &'static str, _len: usize) -> std::result::Result<Self::SerializeStructVariant, SpecError> { unsupported("enum record") }
+}
+
+// 推断单个字段的(类型, 长度, 小数位数); 仅支持dbase可序列化的类型
+struct KindProbe;
+
+type ProbeOk = (DbfFieldKind, u8, u8);
+
+impl ser::Serializer for KindProbe {
+    type Ok = ProbeOk;
+    type Error = SpecError;
+    type SerializeSeq = Impossible<ProbeOk, SpecError>;
+    type SerializeTuple = Impossible<ProbeOk, SpecError>;
+    type SerializeTupleStruct = Impossible<ProbeOk, SpecError>;
+    type SerializeTupleVariant = Impossible<ProbeOk, SpecError>;
+    type SerializeMap = Impossible<ProbeOk, SpecError>;
+    type SerializeStruct = Impossible<ProbeOk, SpecError>;
+    type SerializeStructVariant = Impossible<ProbeOk, SpecError>;
+
+    fn serialize_bool(self, _v: bool) -> std::result::Result<ProbeOk, SpecError> { Ok((DbfFieldKind::Logical, 1, 0)) }
+    fn serialize_i32(self, _v: i32) -> std::result::Result<ProbeOk, SpecError> { Ok((DbfFieldKind::Integer, 4, 0)) }
+    fn serialize_f32(self, _v: f32) -> std::result::Result<ProbeOk, SpecError> { Ok((DbfFieldKind::Float, DEFAULT_NUMERIC_LEN, DEFAULT_NUMERIC_DECIMALS)) }
+    fn serialize_f64(self, _v: f64) -> std::result::Result<ProbeOk, SpecError> { Ok((DbfFieldKind::Numeric, DEFAULT_NUMERIC_LEN, DEFAULT_NUMERIC_DECIMALS)) }
+    fn serialize_str(self, v: &str) -> std::result::Result<ProbeOk, SpecError> {
+        Ok((DbfFieldKind::Character, v.len().clamp(DEFAULT_CHAR_LEN, DBF_MAX_CHAR_LEN) as u8, 0))
+    }
+    fn serialize_some<V: ?Sized + Serialize>(self, value: &V) -> std::result::Result<ProbeOk, SpecError> { value.serialize(self) }
+    fn serialize_newtype_struct<V: ?Sized + Serialize>(self, _name: &'static str, value: &V) -> std::result::Result<ProbeOk, SpecError> { value.serialize(self) }
+    fn serialize_i8(self, _v: i8) -> std::result::Result<ProbeOk, SpecError> { unsupported("i8") }
+    fn serialize_i16(self, _v: i16) -> std::result::Result<ProbeOk, SpecError> { unsupported("i16") }
+    fn serialize_i64(self, _v: i64) -> std::result::Result<ProbeOk, SpecError> { unsupported("i64") }
+    fn serialize_u8(self, _v: u8) -> std::result::Result<ProbeOk, SpecError> { unsupported("u8") }
+    fn serialize_u16(self, _v: u16) -> std::result::Result<ProbeOk, SpecError> { unsupported("u16") }
+    fn serialize_u32(self, _v: u32) -> std::result::Result<ProbeOk, SpecError> { unsupported("u32") }
+    fn serialize_u64(self, _v: u64) -> std::result::Result<ProbeOk, SpecError> { unsupported("u64") }
+    fn serialize_char(self, _v: char) -> std::result::Result<ProbeOk, SpecError> { unsupported("char") }
+    fn serialize_bytes(self, _v: &[u8]) -> std::result::Result<ProbeOk, SpecError> { unsupported("bytes") }
+    fn serialize_none(self) -> std::result::Result<ProbeOk, SpecError> { unsupported("none") }
+    fn serialize_unit(self) -> std::result::Result<ProbeOk, SpecError> { unsupported("unit") }
+    fn serialize_unit_struct(self, _name: &'static str) -> std::result::Result<ProbeOk, SpecError> { unsupported("unit struct") }
+    fn serialize_unit_variant(self, _name: &'static str, _idx: u32, _variant: &'static str) -> std::result::Result<ProbeOk, SpecError> { unsupported("enum") }
+    fn serialize_newtype_variant<V: ?Sized + Serialize>(self, _name: &'static str, _idx: u32, _variant: &'static str, _value: &V) -> std::result::Result<ProbeOk, SpecError> { unsupported("enum") }
+    fn serialize_seq(self, _len: Option<usize>) -> std::result::Result<Self::SerializeSeq, SpecError> { unsupported("seq") }
+    fn serialize_tuple(self, _len: usize) -> std::result::Result<Self::SerializeTuple, SpecError> { unsupported("tuple") }
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> std::result::Result<Self::SerializeTupleStruct, SpecError> { unsupported("tuple struct") }
+    fn serialize_tuple_variant(self, _name: &'static str, _idx: u32, _variant: &'static str, _len: usize) -> std::result::Result<Self::SerializeTupleVariant, SpecError> { unsupported("enum") }
+    fn serialize_map(self, _len: Option<usize>) -> std::result::Result<Self::SerializeMap, SpecError> { unsupported("map") }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> std::result::Result<Self::SerializeStruct, SpecError> { unsupported("nested struct") }
+    fn serialize_struct_variant(self, _name: &'static str, _idx: u32, _variant: &'static str, _len: usize) -> std::result::Result<Self::SerializeStructVariant, SpecError> { unsupported("enum") }
+}
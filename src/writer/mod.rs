@@ -0,0 +1,120 @@
+pub mod dbf_writer;
+pub mod csv_writer;
+pub mod correlator;
+
+use dashmap::DashMap;
+use once_cell::sync::OnceCell;
+use std::path::{Path, PathBuf};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::reader::dbf_reader::DbfFileState;
+
+/*
+写入方加锁约定:
+1. 进程内: 同一路径的写操作通过路径锁串行
+2. 进程间: 写入期间持有文件独占锁(File::lock)
+读取方不加锁, 通过文件头/行结束符判断数据是否完整
+*/
+
+// 为每个写入路径创建一个锁
+static WRITE_PATH_LOCKS: OnceCell<DashMap<PathBuf, Arc<Mutex<()>>>> = OnceCell::new();
+
+// 获取写入路径锁
+pub(crate) fn get_write_path_lock(path: &Path) -> Arc<Mutex<()>> {
+    let locks = WRITE_PATH_LOCKS.get_or_init(DashMap::new);
+    locks.entry(path.to_path_buf()).or_insert_with(|| Arc::new(Mutex::new(()))).clone()
+}
+
+/*
+本进程原地更新记录(不改变记录数)的写入记录: 写入代数 + 文件头记录数 + 写入区间内容哈希,
+读取方据此跳过由自身写回触发的重复分发; 不依赖修改时间(精度不足且可被外部touch)
+*/
+const MAX_SELF_WRITES: usize = 64; // 每个路径最多保留的写入记录数
+
+#[derive(Debug, Clone, Copy)]
+struct SelfWrite {
+    generation: u64, // 写入代数, 全局递增
+    num_records: u64, // 写入后的文件头记录数
+    offset: u64, // 写入区间起始偏移
+    len: u64, // 写入区间长度
+    hash: u64, // 写入后区间内容哈希
+}
+
+static SELF_WRITE_GENERATION: AtomicU64 = AtomicU64::new(0);
+static SELF_WRITES: OnceCell<DashMap<PathBuf, VecDeque<SelfWrite>>> = OnceCell::new();
+
+fn self_write_key(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 当前写入代数; 读取方以此为起点, 只识别之后的写回
+pub(crate) fn self_write_generation() -> u64 {
+    SELF_WRITE_GENERATION.load(Ordering::Acquire)
+}
+
+// 记录一次本进程写回: 写入后记录数为num_records, 区间[offset, offset+bytes.len())的内容为bytes
+pub(crate) fn mark_self_write(path: &Path, num_records: u64, offset: u64, bytes: &[u8]) {
+    let writes = SELF_WRITES.get_or_init(DashMap::new);
+    let mut entry = writes.entry(self_write_key(path)).or_default();
+    if entry.len() >= MAX_SELF_WRITES {
+        entry.pop_front();
+    }
+    let generation = SELF_WRITE_GENERATION.fetch_add(1, Ordering::AcqRel) + 1;
+    entry.push_back(SelfWrite { generation, num_records, offset, len: bytes.len() as u64, hash: content_hash(bytes) });
+}
+
+// 文件状态从last_seen到current的变化是否全部由本进程写回产生; seen_gen为已处理的写入代数, 调用后推进到最新
+pub(crate) fn is_self_write(path: &Path, last_seen: &DbfFileState, current: &DbfFileState, seen_gen: &mut u64) -> bool {
+    let Some(writes) = SELF_WRITES.get().and_then(|w| w.get(&self_write_key(path)).map(|w| w.clone())) else {
+        return false;
+    };
+    let pending = writes.iter().filter(|w| w.generation > *seen_gen).copied().collect::<Vec<_>>();
+    let Some(last) = pending.last() else {
+        return false;
+    };
+    *seen_gen = last.generation;
+    // 原地更新不改变文件结构
+    let same_layout = last_seen.file_len == current.file_len && last_seen.num_records == current.num_records
+        && last_seen.header_len == current.header_len && last_seen.record_len == current.record_len;
+    if !same_layout || pending.iter().any(|w| w.num_records != current.num_records) {
+        return false;
+    }
+    let Ok(mut fd) = File::open(path) else {
+        return false;
+    };
+    // 同一区间被多次写回时只校验最后一次
+    pending.iter().enumerate()
+        .filter(|(i, w)| !pending[i + 1..].iter().any(|later| later.offset == w.offset && later.len == w.len))
+        .all(|(_, w)| {
+            let mut buf = vec![0u8; w.len as usize];
+            fd.seek(SeekFrom::Start(w.offset)).and_then(|_| fd.read_exact(&mut buf)).is_ok() && content_hash(&buf) == w.hash
+        })
+}
+
+/// 记录写入接口, 供请求/应答关联等上层逻辑统一使用CSV/DBF写入
+pub trait RecordWriter<T>: Send + Sync {
+    /// 追加单条记录
+    fn write_record(&self, record: &T) -> anyhow::Result<()>;
+}
+
+impl<T: serde::Serialize> RecordWriter<T> for csv_writer::CsvWriter<T> {
+    fn write_record(&self, record: &T) -> anyhow::Result<()> {
+        self.append(record)
+    }
+}
+
+impl<T: serde::Serialize> RecordWriter<T> for dbf_writer::DbfWriter<T> {
+    fn write_record(&self, record: &T) -> anyhow::Result<()> {
+        self.append(record).map(|_| ())
+    }
+}